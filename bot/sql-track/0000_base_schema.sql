@@ -1,4 +1,4 @@
-CREATE TABLE track_numbers (
+CREATE TABLE IF NOT EXISTS track_numbers (
     chat_id INTEGER,
     packed BLOB,
     track_number TEXT,
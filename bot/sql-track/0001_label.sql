@@ -0,0 +1 @@
+ALTER TABLE track_numbers ADD COLUMN label TEXT;
@@ -3,9 +3,20 @@
 
 use crate::{AppContext, CachedFile, context_ext::ContextExt};
 use fruityger::format::Format;
-use grammers_client::grammers_tl_types::enums::InputBotInlineMessageId;
-use mystbot_core::inline_message_ext::InlineMessageExt;
+use grammers_client::{
+    button,
+    grammers_tl_types::enums::InputBotInlineMessageId,
+    reply_markup::{self, ReplyMarkup},
+    types::CallbackQuery,
+};
+use mystbot_core::{
+    callback, callback_payload,
+    callback_query_ext::{CallbackAnswer, CallbackQueryExt},
+    inline_message_ext::InlineMessageExt,
+    progress::ProgressReporter,
+};
 use tokio::sync::mpsc;
+use tracing::Instrument;
 
 pub type DownloadFunc<T, F> = fn(AppContext, T, mpsc::Sender<String>, bool) -> F;
 
@@ -14,6 +25,33 @@ pub struct DownloadedTrack {
     pub cover_file: Option<CachedFile>,
 }
 
+callback_payload! {
+    /// Buttons of inline messages with tracks
+    pub enum MusicButton: 1 {
+        Downloading = 0,
+    }
+}
+
+/// Keyboard of inline messages with tracks that are being downloaded
+pub fn downloading_markup(context: &AppContext) -> anyhow::Result<impl ReplyMarkup> {
+    Ok(reply_markup::inline(vec![vec![button::inline(
+        context.text("music.downloading", &[]),
+        callback::encode("music", &MusicButton::Downloading)?,
+    )]]))
+}
+
+pub async fn music_button(context: AppContext, query: CallbackQuery) -> anyhow::Result<()> {
+    let answer = match callback::decode::<MusicButton>(query.data()) {
+        Some(MusicButton::Downloading) => {
+            CallbackAnswer::toast(context.text("music.please_wait", &[]))
+        }
+        None => CallbackAnswer::toast(context.text("music.button_outdated", &[])),
+    };
+    context.client.answer_callback_query(&query, answer).await?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "download_cover", skip(context))]
 async fn get_cover(context: AppContext, url: &str) -> anyhow::Result<CachedFile> {
     let dir = tempfile::tempdir()?;
     let response = reqwest::get(url).await?;
@@ -33,12 +71,17 @@ pub async fn get_downloaded_track<
     cover_url: Option<String>,
     get_track: Func,
 ) -> anyhow::Result<DownloadedTrack> {
-    let track_file = if refresh {
-        get_track(context.clone()).await?
+    let cached = if refresh {
+        None
     } else {
-        match context.get_cached_file(&track_url).await {
-            Some(audio) => audio,
-            None => get_track(context.clone()).await?,
+        context.get_cached_file(&track_url).await
+    };
+    let track_file = match cached {
+        Some(audio) => audio,
+        None => {
+            get_track(context.clone())
+                .instrument(tracing::info_span!("download", url = %track_url))
+                .await?
         }
     };
 
@@ -61,6 +104,7 @@ pub async fn get_downloaded_track<
     })
 }
 
+#[tracing::instrument(skip_all, fields(title = %title, artist = %artist))]
 pub async fn retry_send_inline_with_progress<
     T: Clone,
     F: Future<Output = anyhow::Result<DownloadedTrack>>,
@@ -72,51 +116,61 @@ pub async fn retry_send_inline_with_progress<
     duration_ms: u64,
     data: T,
     download_func: DownloadFunc<T, F>,
-) -> anyhow::Result<()> {
-    let (tx, mut rx) = mpsc::channel(16);
-    {
-        let message_id = message_id.clone();
-        let client = context.client.clone();
-        tokio::spawn(async move {
-            while let Some(m) = rx.recv().await {
-                let _ = client
-                    .edit_inline_message_ext(
-                        message_id.clone(),
-                        m,
-                        Some("Скачиваем...".to_string()),
-                        None,
-                    )
-                    .await;
-            }
-        });
-    }
+) -> anyhow::Result<bool> {
+    let _pending = context.mark_pending(message_id.clone());
 
+    let markup = downloading_markup(&context)?;
     let mut sent = false;
     for i in 0..2 {
-        if let Ok(downloaded_track) =
-            download_func(context.clone(), data.clone(), tx.clone(), i == 1).await
-        {
-            sent = context
-                .send_downloaded_track(
-                    downloaded_track,
-                    message_id.clone(),
-                    title.clone(),
-                    artist.clone(),
-                    duration_ms,
-                )
-                .await?;
-            if sent {
-                break;
+        let progress = ProgressReporter::new(context.client.clone(), message_id.clone())
+            .reply_markup(&markup)
+            .start();
+        let downloaded_track =
+            download_func(context.clone(), data.clone(), progress.sender(), i == 1).await;
+        progress.stop().await;
+
+        match downloaded_track {
+            Ok(downloaded_track) => {
+                sent = context
+                    .send_downloaded_track(
+                        downloaded_track,
+                        message_id.clone(),
+                        title.clone(),
+                        artist.clone(),
+                        duration_ms,
+                    )
+                    .await?;
+                if sent {
+                    break;
+                }
+                tracing::warn!(attempt = i + 1, "failed to send track");
             }
-        };
+            Err(e) => tracing::warn!(attempt = i + 1, error = ?e, "failed to download track"),
+        }
     }
 
     if !sent {
+        tracing::error!("giving up on track");
         context
             .client
-            .edit_inline_message_ext(message_id.clone(), "Не удалось скачать трек", None, None)
+            .edit_inline_message_ext(
+                message_id.clone(),
+                context.text("music.download_failed", &[]),
+            )
             .await?;
     }
 
-    Ok(())
+    Ok(sent)
+}
+
+/// Count the download in `mystbot_downloads_total` by provider and whether the track was sent
+pub fn record_download(context: &AppContext, provider: &str, result: &anyhow::Result<bool>) {
+    let outcome = match result {
+        Ok(true) => "success",
+        _ => "failed",
+    };
+    context.metrics().inc(
+        "mystbot_downloads_total",
+        &[("provider", provider), ("outcome", outcome)],
+    );
 }
@@ -5,12 +5,15 @@ use grammers_client::{
     grammers_tl_types::{self, enums::InputBotInlineMessageId},
     types::Attribute,
 };
-use mystbot_core::inline_message_ext::InlineMessageExt;
+use mystbot_core::inline_message_ext::{InlineEdit, InlineMessageExt};
 
 use crate::{AppContext, CachedFile, audio_common::DownloadedTrack};
 use std::{path::Path, time::Duration};
 
 pub trait ContextExt {
+    /// Count a cache lookup in `mystbot_cache_lookups_total`
+    fn record_cache_lookup(&self, cache: &str, hit: bool);
+
     fn get_cached_file(&self, url: &str) -> impl Future<Output = Option<CachedFile>>;
 
     fn upload_cached_file(
@@ -31,10 +34,27 @@ pub trait ContextExt {
 }
 
 impl ContextExt for AppContext {
+    fn record_cache_lookup(&self, cache: &str, hit: bool) {
+        let result = if hit { "hit" } else { "miss" };
+        self.metrics().inc(
+            "mystbot_cache_lookups_total",
+            &[("cache", cache), ("result", result)],
+        );
+    }
+
     async fn get_cached_file(&self, url: &str) -> Option<CachedFile> {
-        Some(self.state.read().await.file_cache.get(url)?.value().clone())
+        let cached_file = self
+            .state
+            .read()
+            .await
+            .file_cache
+            .get(url)
+            .map(|v| v.value().clone());
+        self.record_cache_lookup("file_cache", cached_file.is_some());
+        cached_file
     }
 
+    #[tracing::instrument(name = "upload", skip(self, path), fields(path = %path.display()))]
     async fn upload_cached_file(
         &self,
         url: &str,
@@ -50,6 +70,10 @@ impl ContextExt for AppContext {
             .await
             .file_cache
             .insert(url.to_owned(), cached_file.clone());
+        if let Ok(metadata) = tokio::fs::metadata(path).await {
+            self.metrics()
+                .add("mystbot_uploaded_bytes_total", &[], metadata.len() as f64);
+        }
         Ok(cached_file)
     }
 
@@ -65,9 +89,7 @@ impl ContextExt for AppContext {
             .client
             .edit_inline_message_ext(
                 message_id,
-                "",
-                None,
-                Some(grammers_tl_types::enums::InputMedia::UploadedDocument(
+                InlineEdit::text("").media(grammers_tl_types::enums::InputMedia::UploadedDocument(
                     grammers_tl_types::types::InputMediaUploadedDocument {
                         nosound_video: false,
                         force_file: false,
@@ -52,7 +52,7 @@ pub async fn download_track(
         track.url.clone(),
         Some(track.cover_url.clone()),
         |context| async move {
-            tx.send("Скачиваем аудио".to_string()).await?;
+            tx.send(context.text("music.progress.audio", &[])).await?;
 
             let stream = match module_type {
                 ModuleType::Yandex => {
@@ -85,7 +85,7 @@ pub async fn download_track(
             let mut track_path =
                 fruityger::save_audio_stream(stream, workdir.path(), &filename_temp).await?;
 
-            tx.send("Скачиваем обложку".to_string()).await?;
+            tx.send(context.text("music.progress.cover", &[])).await?;
 
             let cover_path = fruityger::save_cover(
                 reqwest::get(&track.cover_url).await?,
@@ -94,22 +94,25 @@ pub async fn download_track(
             )
             .await?;
 
-            tx.send("Добавляем метаданные".to_string()).await?;
+            tx.send(context.text("music.progress.metadata", &[]))
+                .await?;
 
-            track_path = fruityger::remux(
-                workdir.path(),
-                &track_path,
-                Some(&cover_path.0),
-                format.clone(),
-                &filename,
-                Metadata {
-                    title: track.title.clone(),
-                    artist: track.artists[0].name.clone(),
-                    ..Default::default()
-                },
-            )?;
+            track_path = tracing::info_span!("remux").in_scope(|| {
+                fruityger::remux(
+                    workdir.path(),
+                    &track_path,
+                    Some(&cover_path.0),
+                    format.clone(),
+                    &filename,
+                    Metadata {
+                        title: track.title.clone(),
+                        artist: track.artists[0].name.clone(),
+                        ..Default::default()
+                    },
+                )
+            })?;
 
-            tx.send("Загружаем файл".to_string()).await?;
+            tx.send(context.text("music.progress.upload", &[])).await?;
 
             context
                 .upload_cached_file(&track.url, &track_path, format.mime_type())
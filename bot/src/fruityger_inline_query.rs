@@ -1,70 +1,81 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
-use crate::{AppContext, fruityger_common::ModuleType, return_response};
-use grammers_client::{
-    button, reply_markup,
-    types::{
-        InlineQuery,
-        inline::query::{Article, InlineResult},
-    },
+use crate::{AppContext, audio_common, fruityger_common::ModuleType, return_response};
+use grammers_client::types::{
+    InlineQuery,
+    inline::query::{Article, InlineResult},
 };
-use mystbot_core::inline_audio::InlineAudio;
+use mystbot_core::{inline_audio::InlineAudio, inline_command};
 
 pub async fn run(context: AppContext, query: InlineQuery, args: Vec<String>) -> anyhow::Result<()> {
     if args.is_empty() {
-        return_response!(query, "Введите запрос");
+        return_response!(query, context.text("music.enter_query", &[]));
     }
 
     let (service, search_query) = if ["yandex", "qobuz", "hifi"].contains(&args[0].as_str()) {
         if args.len() < 2 {
-            return_response!(query, "Введите запрос");
+            return_response!(query, context.text("music.enter_query", &[]));
         }
         (ModuleType::try_from(args[0].as_str())?, args[1..].join(" "))
     } else {
         (ModuleType::HifiQobuz, args.join(" "))
     };
 
+    let audio_query =
+        mystbot_core::inline_query::InlineQuery::new(query.clone(), context.client.clone());
+    let needed = audio_query.needed(10);
+    let key = format!("{} {search_query}", service.as_str());
+    let search_query = &search_query;
+    let searches = context.state.read().await.fruityger_searches.clone();
     let results = match service.as_str() {
         "yandex" => {
-            if let Some(client) = &context.state.read().await.fruityger_clients.yandex {
-                client.search(&search_query, 0).await
-            } else {
-                return_response!(query, "Сервис недоступен");
-            }
+            let Some(client) = context.state.read().await.fruityger_clients.yandex.clone() else {
+                return_response!(query, context.text("music.service_unavailable", &[]));
+            };
+            let client = &client;
+            searches
+                .get_or_search(&key, needed, |page| async move {
+                    client.search(search_query, page).await.map(|r| r.tracks)
+                })
+                .await
         }
         "hifi" | "qobuz" => {
-            if let Some(client) = &context.state.read().await.fruityger_clients.hifi {
-                client.search(&search_query, 0).await
-            } else {
-                return_response!(query, "Сервис недоступен");
-            }
+            let Some(client) = context.state.read().await.fruityger_clients.hifi.clone() else {
+                return_response!(query, context.text("music.service_unavailable", &[]));
+            };
+            let client = &client;
+            searches
+                .get_or_search(&key, needed, |page| async move {
+                    client.search(search_query, page).await.map(|r| r.tracks)
+                })
+                .await
         }
         _ => {
-            return_response!(query, "Такого сервиса не существует");
+            return_response!(query, context.text("music.no_such_service", &[]));
         }
     };
     let Ok(results) = results else {
-        return_response!(query, "Сервис недоступен");
+        return_response!(query, context.text("music.service_unavailable", &[]));
     };
+    let (tracks, next_offset) = audio_query.page(results.iter().cloned(), 10);
 
-    let inline_results: Vec<_> = results
-        .tracks
+    let markup = audio_common::downloading_markup(&context)?;
+    let inline_results: Vec<_> = tracks
         .iter()
-        .take(10)
         .map(|t| {
             InlineAudio::new("https://s.myst33d.ru/placeholder.mp3".to_string())
-                .id(format!("fruityger|{}|{}", service.as_str(), t.id))
+                .id(inline_command::result_id(
+                    "music",
+                    [service.as_str(), t.id.as_str()],
+                ))
                 .title(t.title.clone())
                 .performer(t.artists[0].name.clone())
-                .reply_markup(&reply_markup::inline(vec![vec![button::inline(
-                    "Скачиваем...",
-                    b"0",
-                )]]))
+                .reply_markup(&markup)
         })
         .collect();
 
-    for track in results.tracks.into_iter() {
+    for track in tracks.into_iter() {
         context
             .state
             .read()
@@ -73,9 +84,11 @@ pub async fn run(context: AppContext, query: InlineQuery, args: Vec<String>) ->
             .insert(track.id.clone(), track);
     }
 
-    let audio_query =
-        mystbot_core::inline_query::InlineQuery::new(query.clone(), context.client.clone());
-    audio_query.answer(inline_results).send().await?;
+    audio_query
+        .answer(inline_results)
+        .next_offset(next_offset)
+        .send()
+        .await?;
 
     Ok(())
 }
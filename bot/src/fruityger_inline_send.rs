@@ -3,10 +3,11 @@
 
 use crate::{
     AppContext, audio_common,
+    context_ext::ContextExt,
     fruityger_common::{self, ModuleType},
 };
 use grammers_client::types::InlineSend;
-use mystbot_core::inline_message_ext::InlineMessageExt;
+use mystbot_core::inline_message_ext::{InlineEdit, InlineMessageExt};
 
 pub async fn run(context: AppContext, send: InlineSend, args: Vec<String>) -> anyhow::Result<()> {
     let message_id = send.message_id().unwrap();
@@ -14,32 +15,36 @@ pub async fn run(context: AppContext, send: InlineSend, args: Vec<String>) -> an
     let Ok(module_type) = ModuleType::try_from(args[0].as_str()) else {
         context
             .client
-            .edit_inline_message_ext(message_id, "Неизвестный сервис", Some("Скачиваем..."), None)
+            .edit_inline_message_ext(
+                message_id,
+                InlineEdit::text(context.text("music.unknown_service", &[]))
+                    .reply_markup(&audio_common::downloading_markup(&context)?),
+            )
             .await?;
         return Ok(());
     };
 
-    let Some(track) = context
+    let track = context
         .state
         .read()
         .await
         .fruityger_cache
         .get(&args[1])
-        .map(|v| v.value().clone())
-    else {
+        .map(|v| v.value().clone());
+    context.record_cache_lookup("fruityger_cache", track.is_some());
+    let Some(track) = track else {
         context
             .client
             .edit_inline_message_ext(
                 message_id,
-                "Устаревшее сообщение",
-                Some("Скачиваем..."),
-                None,
+                InlineEdit::text(context.text("music.outdated", &[]))
+                    .reply_markup(&audio_common::downloading_markup(&context)?),
             )
             .await?;
         return Ok(());
     };
 
-    audio_common::retry_send_inline_with_progress(
+    let result = audio_common::retry_send_inline_with_progress(
         context.clone(),
         message_id.clone(),
         track.title.clone(),
@@ -48,5 +53,7 @@ pub async fn run(context: AppContext, send: InlineSend, args: Vec<String>) -> an
         (track, module_type),
         fruityger_common::download_track,
     )
-    .await
+    .await;
+    audio_common::record_download(&context, module_type.as_str(), &result);
+    result.map(|_| ())
 }
@@ -1,68 +1,73 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
-use grammers_client::{
-    button, reply_markup,
-    types::{
-        InlineQuery,
-        inline::query::{Article, InlineResult},
-    },
+use grammers_client::types::{
+    InlineQuery,
+    inline::query::{Article, InlineResult},
 };
 use lucida_api::{LucidaClient, LucidaService};
-use mystbot_core::inline_audio::InlineAudio;
+use mystbot_core::{inline_audio::InlineAudio, inline_command};
 
-use crate::{AppContext, return_response, sha1};
+use crate::{AppContext, audio_common, return_response, sha1};
 
 pub async fn run(context: AppContext, query: InlineQuery, args: Vec<String>) -> anyhow::Result<()> {
     if args.is_empty() {
-        return_response!(query, "Введите запрос");
+        return_response!(query, context.text("music.enter_query", &[]));
     }
 
     let lucida = LucidaClient::new();
-    let (service, search_query) = if let Ok(service) = LucidaService::try_from(args[0].as_str()) {
-        if args.len() < 2 {
-            return_response!(query, "Введите запрос");
-        }
-        (service, args[1..].join(" "))
-    } else {
-        (LucidaService::Tidal, args.join(" "))
-    };
+    let (service, service_name, search_query) =
+        if let Ok(service) = LucidaService::try_from(args[0].as_str()) {
+            if args.len() < 2 {
+                return_response!(query, context.text("music.enter_query", &[]));
+            }
+            (service, args[0].as_str(), args[1..].join(" "))
+        } else {
+            (LucidaService::Tidal, "tidal", args.join(" "))
+        };
 
-    let Ok(countries) = lucida.fetch_countries(service.clone()).await else {
-        return_response!(query, "Сервис недоступен");
-    };
-    if countries.countries.is_empty() {
-        return_response!(query, "Сервис недоступен");
-    }
-
-    let Ok(results) = lucida
-        .fetch_search(service, &countries.countries[0].code, &search_query)
-        .await
-    else {
-        return_response!(query, "Сервис недоступен");
+    let audio_query =
+        mystbot_core::inline_query::InlineQuery::new(query.clone(), context.client.clone());
+    let key = format!("{service_name} {search_query}");
+    let (lucida, service, search_query) = (&lucida, &service, &search_query);
+    let searches = context.state.read().await.lucida_searches.clone();
+    let results = searches
+        .get_or_search(&key, audio_query.needed(10), |page| async move {
+            // Lucida returns all results in one search, there is no next page
+            if page > 0 {
+                return anyhow::Ok(vec![]);
+            }
+            let countries = lucida.fetch_countries(service.clone()).await?;
+            let Some(country) = countries.countries.first() else {
+                anyhow::bail!("no countries available for {service_name}");
+            };
+            let results = lucida
+                .fetch_search(service.clone(), &country.code, search_query)
+                .await?;
+            anyhow::Ok(results.results.tracks)
+        })
+        .await;
+    let Ok(results) = results else {
+        return_response!(query, context.text("music.service_unavailable", &[]));
     };
-    if results.results.tracks.is_empty() {
-        return_response!(query, "Не найдено треков по данному запросу");
+    if results.is_empty() {
+        return_response!(query, context.text("music.nothing_found", &[]));
     }
+    let (tracks, next_offset) = audio_query.page(results.iter().cloned(), 10);
 
-    let inline_results: Vec<_> = results
-        .results
-        .tracks
+    let markup = audio_common::downloading_markup(&context)?;
+    let inline_results: Vec<_> = tracks
         .iter()
-        .take(10)
         .map(|t| {
             InlineAudio::new("https://s.myst33d.ru/placeholder.mp3".to_string())
-                .id(format!("lucida|{}", &sha1!(&t.url.clone())[..16]))
+                .id(inline_command::result_id("lucida", [&sha1!(&t.url)[..16]]))
                 .title(t.title.clone())
                 .performer(t.artists[0].name.clone())
-                .reply_markup(&reply_markup::inline(vec![vec![button::inline(
-                    "Скачиваем...",
-                    b"0",
-                )]]))
+                .reply_markup(&markup)
         })
         .collect();
 
-    for track in results.results.tracks.into_iter() {
+    for track in tracks.into_iter() {
         context
             .state
             .read()
@@ -71,9 +76,11 @@ pub async fn run(context: AppContext, query: InlineQuery, args: Vec<String>) ->
             .insert(sha1!(&track.url)[..16].to_string(), track);
     }
 
-    let audio_query =
-        mystbot_core::inline_query::InlineQuery::new(query.clone(), context.client.clone());
-    audio_query.answer(inline_results).send().await?;
+    audio_query
+        .answer(inline_results)
+        .next_offset(next_offset)
+        .send()
+        .await?;
 
     Ok(())
 }
@@ -1,34 +1,34 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
-use crate::{AppContext, audio_common, lucida_common};
+use crate::{AppContext, audio_common, context_ext::ContextExt, lucida_common};
 use grammers_client::types::InlineSend;
-use mystbot_core::inline_message_ext::InlineMessageExt;
+use mystbot_core::inline_message_ext::{InlineEdit, InlineMessageExt};
 
 pub async fn run(context: AppContext, send: InlineSend, args: Vec<String>) -> anyhow::Result<()> {
     let message_id = send.message_id().unwrap();
 
-    let Some(track) = context
+    let track = context
         .state
         .read()
         .await
         .lucida_cache
         .get(&args[0])
-        .map(|v| v.value().clone())
-    else {
+        .map(|v| v.value().clone());
+    context.record_cache_lookup("lucida_cache", track.is_some());
+    let Some(track) = track else {
         context
             .client
             .edit_inline_message_ext(
                 message_id,
-                "Устаревшее сообщение",
-                Some("Скачиваем..."),
-                None,
+                InlineEdit::text(context.text("music.outdated", &[]))
+                    .reply_markup(&audio_common::downloading_markup(&context)?),
             )
             .await?;
         return Ok(());
     };
 
-    audio_common::retry_send_inline_with_progress(
+    let result = audio_common::retry_send_inline_with_progress(
         context.clone(),
         message_id.clone(),
         track.title.clone(),
@@ -37,5 +37,7 @@ pub async fn run(context: AppContext, send: InlineSend, args: Vec<String>) -> an
         track.clone(),
         lucida_common::download_track,
     )
-    .await
+    .await;
+    audio_common::record_download(&context, "lucida", &result);
+    result.map(|_| ())
 }
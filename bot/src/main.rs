@@ -11,39 +11,47 @@ mod lucida_inline_query;
 mod lucida_inline_send;
 mod modules;
 
-use clokwerk::AsyncScheduler;
+use anyhow::Context as _;
 use dashmap::DashMap;
-use grammers_client::types::{
-    inline::query::{Article, InlineResult},
-    media::Uploaded,
+use grammers_client::{
+    Client,
+    session::PackedType,
+    types::{
+        Chat, InlineQuery, Message, PackedChat,
+        inline::query::{Article, InlineResult},
+        media::Uploaded,
+    },
+};
+use mystbot_core::{
+    Context,
+    args::{ArgSpec, Args, ArgsError, FromArgs},
+    command::{CommandScope, PeerMenu},
+    conversation_store::SqliteConversationStore,
+    error_reporter::ErrorReporter,
+    i18n::Catalog,
+    inline_query::SearchCache,
+    job_store::SqliteJobStore,
+    role::Role,
+    session_store::{FileSessionStore, SessionStore, SqliteSessionStore},
+    user_store::SqliteUserStore,
 };
-use mystbot_core::Context;
 use serde::Deserialize;
 use sqlx::{Pool, Sqlite, SqlitePool};
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tokio::{fs, sync::RwLock};
+use tracing_subscriber::EnvFilter;
 
 #[macro_export]
 macro_rules! return_response {
-    ($q:ident, $msg:literal) => {
-        $q.answer([InlineResult::from(Article::new($msg, $msg))])
+    ($q:ident, $msg:expr) => {
+        let msg: String = $msg.into();
+        $q.answer([InlineResult::from(Article::new(msg.as_str(), msg.as_str()))])
             .send()
             .await?;
         return Ok(());
     };
 }
 
-#[macro_export]
-macro_rules! return_unit_response {
-    ($q:ident, $msg:literal) => {
-        let _ = $q
-            .answer([InlineResult::from(Article::new($msg, $msg))])
-            .send()
-            .await;
-        return;
-    };
-}
-
 #[macro_export]
 macro_rules! sha1 {
     ($d:expr) => {{
@@ -54,6 +62,9 @@ macro_rules! sha1 {
     }};
 }
 
+/// How long inline search results are kept for scrolling through pages
+const SEARCH_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
 type AppState = Arc<RwLock<State>>;
 type AppContext = Context<AppState>;
 
@@ -70,7 +81,9 @@ struct State {
     track_db: Pool<Sqlite>,
     file_cache: DashMap<String, CachedFile>,
     lucida_cache: DashMap<String, lucida_api::Track>,
+    lucida_searches: Arc<SearchCache<lucida_api::Track>>,
     fruityger_cache: DashMap<String, fruityger::Track>,
+    fruityger_searches: Arc<SearchCache<fruityger::Track>>,
     fruityger_clients: FruitygerClients,
 }
 
@@ -79,15 +92,209 @@ struct Config {
     token: String,
     api_id: i32,
     api_hash: String,
+    admin_chat: Option<AdminChatConfig>,
+    #[serde(default)]
+    session: SessionConfig,
+    #[serde(default)]
+    roles: RolesConfig,
+    #[serde(default)]
+    log: LogConfig,
+    /// Address of the Prometheus metrics endpoint, e.g. `127.0.0.1:9100`, disabled if not set
+    metrics: Option<SocketAddr>,
     fruityger: FruitygerConfig,
 }
 
+/// Chat that gets error reports, either `{ username = "..." }` resolved at startup
+/// or `{ id = ..., type = "user", access_hash = ... }`, basic groups have no access hash
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AdminChatConfig {
+    Username {
+        username: String,
+    },
+    Id {
+        id: i64,
+        #[serde(rename = "type", default)]
+        ty: ChatType,
+        access_hash: Option<i64>,
+    },
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+enum ChatType {
+    #[default]
+    User,
+    Group,
+    Supergroup,
+    Channel,
+}
+
+impl AdminChatConfig {
+    async fn resolve(self, client: &Client) -> anyhow::Result<PackedChat> {
+        match self {
+            AdminChatConfig::Username { username } => Ok(client
+                .resolve_username(&username)
+                .await?
+                .with_context(|| format!("there is no chat @{username}"))?
+                .pack()),
+            AdminChatConfig::Id {
+                id,
+                ty,
+                access_hash,
+            } => Ok(PackedChat {
+                ty: match ty {
+                    ChatType::User => PackedType::User,
+                    ChatType::Group => PackedType::Chat,
+                    ChatType::Supergroup => PackedType::Megagroup,
+                    ChatType::Channel => PackedType::Broadcast,
+                },
+                id,
+                access_hash,
+            }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "storage", rename_all = "lowercase")]
+enum SessionConfig {
+    File { path: String },
+    Sqlite,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig::File {
+            path: "teobot.session".to_owned(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct LogConfig {
+    format: LogFormat,
+    /// Filter like `info` or `mystbot=debug,mystbot_core=info`, `RUST_LOG` takes precedence
+    level: String,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            format: LogFormat::Pretty,
+            level: "info".to_owned(),
+        }
+    }
+}
+
+fn init_tracing(config: &LogConfig) {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.level));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    match config.format {
+        LogFormat::Pretty => subscriber.pretty().init(),
+        LogFormat::Json => subscriber.json().init(),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct RolesConfig {
+    owners: Vec<i64>,
+    admins: Vec<i64>,
+    /// Minimum role needed to download music
+    music: String,
+}
+
+impl Default for RolesConfig {
+    fn default() -> Self {
+        RolesConfig {
+            owners: vec![],
+            admins: vec![],
+            music: "user".to_owned(),
+        }
+    }
+}
+
 #[derive(Deserialize)]
 struct FruitygerConfig {
     hifi: Option<fruityger::hifi::Config>,
     yandex: Option<fruityger::yandex::Config>,
 }
 
+async fn inline_fallback(context: AppContext, query: InlineQuery) -> anyhow::Result<()> {
+    if query.text().trim().is_empty() {
+        return_response!(query, context.text("inline.enter_command", &[]));
+    }
+    return_response!(query, context.text("inline.unknown_command", &[]));
+}
+
+async fn start(context: AppContext, message: Message) -> anyhow::Result<()> {
+    message.reply(context.text("start.greeting", &[])).await?;
+    Ok(())
+}
+
+struct LanguageArgs {
+    language: Option<String>,
+}
+
+impl FromArgs for LanguageArgs {
+    fn spec() -> ArgSpec {
+        ArgSpec::new().optional("language", "args.language")
+    }
+
+    fn from_args(mut args: Args) -> Result<Self, ArgsError> {
+        Ok(Self {
+            language: args.take_optional("language")?,
+        })
+    }
+}
+
+async fn language(context: AppContext, message: Message, args: LanguageArgs) -> anyhow::Result<()> {
+    let Some(sender) = message.sender() else {
+        return Ok(());
+    };
+    let languages = context.locales().languages().join(", ");
+
+    let text = match args.language.as_deref() {
+        None => context.text(
+            "language.current",
+            &[
+                ("language", context.language().into()),
+                ("languages", languages.into()),
+            ],
+        ),
+        Some(language) => {
+            let language = (language != "auto").then_some(language);
+            if context
+                .locales()
+                .set_language(sender.id(), language)
+                .await?
+            {
+                let lang_code = match &sender {
+                    Chat::User(user) => user.lang_code(),
+                    _ => None,
+                };
+                let language = context.locales().language(sender.id(), lang_code).await;
+                context.locales().text(&language, "language.changed", &[])
+            } else {
+                context.text("language.unknown", &[("languages", languages.into())])
+            }
+        }
+    };
+    message.reply(text).await?;
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() {
     let config: Config = toml::from_slice(
@@ -96,6 +303,8 @@ async fn main() {
             .unwrap(),
     )
     .unwrap();
+    init_tracing(&config.log);
+
     let mut fruityger_clients = FruitygerClients::default();
 
     if let Some(config) = config.fruityger.hifi {
@@ -106,60 +315,124 @@ async fn main() {
         fruityger_clients.yandex = Some(fruityger::yandex::Yandex::new(config))
     }
 
-    let (_, mut app) = mystbot_core::MystbotCore::connect(
+    let track_db = SqlitePool::connect("sqlite://track.db")
+        .await
+        .expect("failed to open track db");
+    sqlx::migrate!("./sql-track")
+        .run(&track_db)
+        .await
+        .expect("failed to migrate track db");
+    let session_store: Arc<dyn SessionStore> = match config.session {
+        SessionConfig::File { path } => Arc::new(FileSessionStore::new(path)),
+        SessionConfig::Sqlite => Arc::new(
+            SqliteSessionStore::new(track_db.clone(), "bot")
+                .await
+                .expect("failed to open session table"),
+        ),
+    };
+
+    let conversation_store = SqliteConversationStore::new(track_db.clone())
+        .await
+        .expect("failed to open conversations table");
+
+    let job_store = SqliteJobStore::new(track_db.clone())
+        .await
+        .expect("failed to open jobs table");
+
+    let language_store =
+        SqliteUserStore::<String>::new(track_db.clone(), "user_languages", "language")
+            .await
+            .expect("failed to open user languages table");
+
+    let role_store = SqliteUserStore::<Role>::new(track_db.clone(), "user_roles", "role")
+        .await
+        .expect("failed to open user roles table");
+
+    let peer_menu_store = SqliteUserStore::<PeerMenu>::new(track_db.clone(), "peer_menus", "menu")
+        .await
+        .expect("failed to open peer menus table");
+
+    let state = Arc::new(RwLock::new(State {
+        track_db,
+        file_cache: DashMap::new(),
+        lucida_cache: DashMap::new(),
+        lucida_searches: Arc::new(SearchCache::new(SEARCH_CACHE_TTL)),
+        fruityger_cache: DashMap::new(),
+        fruityger_searches: Arc::new(SearchCache::new(SEARCH_CACHE_TTL)),
+        fruityger_clients,
+    }));
+
+    let (client, mut app) = mystbot_core::MystbotCore::connect(
         &config.token,
         config.api_id,
         &config.api_hash,
-        Arc::new(RwLock::new(State {
-            track_db: SqlitePool::connect("sqlite://track.db")
-                .await
-                .expect("failed to open track db"),
-            file_cache: DashMap::new(),
-            lucida_cache: DashMap::new(),
-            fruityger_cache: DashMap::new(),
-            fruityger_clients,
-        })),
+        session_store,
+        state.clone(),
     )
     .await
     .expect("client initialization failed");
 
-    app.set_inline_query(|context, query| {
-        Box::pin(async move {
-            let args: Vec<_> = query.text().split(" ").map(|s| s.to_string()).collect();
-            if args.is_empty() {
-                return_unit_response!(query, "Введите команду");
-            }
-            let _ = match args[0].as_str() {
-                "lucida" => lucida_inline_query::run(context, query, args[1..].to_vec()).await,
-                "music" => fruityger_inline_query::run(context, query, args[1..].to_vec()).await,
-                _ => {
-                    return_unit_response!(query, "Неизвестная команда");
-                }
-            };
-        })
-    });
-
-    app.set_inline_send(|context, send| {
-        Box::pin(async move {
-            let args: Vec<_> = send.result_id().split("|").map(|s| s.to_string()).collect();
-            let _ = match args[0].as_str() {
-                "lucida" => lucida_inline_send::run(context, send, args[1..].to_vec()).await,
-                "fruityger" => fruityger_inline_send::run(context, send, args[1..].to_vec()).await,
-                _ => Ok(()),
-            };
-        })
-    });
-
-    app.add_command("start", |_, message| {
-        Box::pin(async move {
-            message.reply("Привет!").await.unwrap();
-        })
-    });
+    let mut error_reporter = ErrorReporter::new().reply("error.reply");
+    if let Some(admin_chat) = config.admin_chat {
+        error_reporter = error_reporter.admin_chat(
+            admin_chat
+                .resolve(&client)
+                .await
+                .expect("failed to resolve admin chat"),
+        );
+    }
+    app.set_error_reporter(error_reporter);
+    app.set_conversation_store(Arc::new(conversation_store));
+    app.set_job_store(Arc::new(job_store));
+    app.set_language_store(Arc::new(language_store));
+    app.set_role_store(Arc::new(role_store));
+    app.set_peer_menu_store(Arc::new(peer_menu_store));
+    if let Some(address) = config.metrics {
+        app.set_metrics_address(address);
+    }
+    modules::track::update_tracked_parcels(app.metrics(), &state.read().await.track_db).await;
+    for id in config.roles.owners {
+        app.set_role(id, Role::Owner);
+    }
+    for id in config.roles.admins {
+        app.set_role(id, Role::Admin);
+    }
+    let music_role: Role = config.roles.music.parse().expect("invalid music role");
+    app.set_default_language("ru");
+    app.add_catalog(
+        "ru",
+        Catalog::parse(include_str!("../locales/ru.txt")).expect("invalid ru catalog"),
+    );
+    app.add_catalog(
+        "en",
+        Catalog::parse(include_str!("../locales/en.txt")).expect("invalid en catalog"),
+    );
+    app.set_shutdown_notice("shutdown.notice");
 
-    let mut scheduler = AsyncScheduler::new();
-    mystbot_core::run(
-        Arc::new(app.register(modules::track::register, &mut scheduler)),
-        scheduler,
+    app.add_inline_command("lucida", lucida_inline_query::run, lucida_inline_send::run)
+        .role(music_role);
+    app.add_inline_command(
+        "music",
+        fruityger_inline_query::run,
+        fruityger_inline_send::run,
     )
-    .await;
+    .role(music_role);
+    app.set_inline_fallback(inline_fallback);
+    app.add_callback("music", audio_common::music_button);
+
+    app.set_module("modules.general");
+    app.add_command("start", start)
+        .description("commands.start")
+        .scope(CommandScope::Private);
+    app.add_command_with_args("language", language)
+        .description("commands.language");
+    app.add_help("help.header", "commands.help");
+    app.add_cancel("cancel.reply", "commands.cancel");
+
+    app.set_module("modules.admin");
+    app.add_role_commands("commands.grant", "commands.revoke", "commands.roles");
+
+    mystbot_core::run(Arc::new(app.register(modules::track::register))).await;
+
+    state.read().await.track_db.close().await;
 }
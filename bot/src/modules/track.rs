@@ -2,39 +2,118 @@
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
 use crate::{AppContext, AppState};
-use clokwerk::{AsyncScheduler, Interval};
-use grammers_client::{InputMessage, types::PackedChat};
-use mystbot_core::MystbotCore;
-use sqlx::FromRow;
+use anyhow::Context as _;
+use grammers_client::{
+    InputMessage,
+    types::{Message, PackedChat},
+};
+use mystbot_core::{
+    MystbotCore,
+    args::{ArgSpec, Args, ArgsError, FromArgs},
+    conversation::{ConversationKey, ConversationState, Reply},
+    member::{MemberChange, MemberUpdate},
+    metrics::Metrics,
+    scheduler::Schedule,
+};
+use sqlx::{FromRow, SqlitePool};
+use std::time::Duration;
 use track24::{TrackResponse, TrackResponseInner};
 
 #[derive(Debug, FromRow)]
 struct TrackEntry {
     packed: Vec<u8>,
     track_number: String,
+    label: Option<String>,
 }
 
-async fn track_once(context: AppContext, track_number: &str, chat: PackedChat) {
+/// Escape text typed by users so it is shown as is in markdown messages
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_punctuation() {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn display_name(track_number: &str, label: Option<&str>) -> String {
+    match label {
+        Some(label) => format!("{track_number} ({label})"),
+        None => track_number.to_owned(),
+    }
+}
+
+struct TrackArgs {
+    track_number: Option<String>,
+    label: Option<String>,
+}
+
+impl FromArgs for TrackArgs {
+    fn spec() -> ArgSpec {
+        ArgSpec::new()
+            .optional("track_number", "args.track_number")
+            .rest("label", "args.label")
+    }
+
+    fn from_args(mut args: Args) -> Result<Self, ArgsError> {
+        Ok(Self {
+            track_number: args.take_optional("track_number")?,
+            label: args.take_optional("label")?,
+        })
+    }
+}
+
+struct TrackNumberArgs {
+    track_number: String,
+}
+
+impl FromArgs for TrackNumberArgs {
+    fn spec() -> ArgSpec {
+        ArgSpec::new().required("track_number", "args.track_number")
+    }
+
+    fn from_args(mut args: Args) -> Result<Self, ArgsError> {
+        Ok(Self {
+            track_number: args.take("track_number")?,
+        })
+    }
+}
+
+/// Set the `mystbot_tracked_parcels` gauge to the number of tracked numbers
+pub async fn update_tracked_parcels(metrics: &Metrics, track_db: &SqlitePool) {
+    match sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM track_numbers")
+        .fetch_one(track_db)
+        .await
+    {
+        Ok(count) => metrics.set("mystbot_tracked_parcels", &[], count as f64),
+        Err(e) => tracing::warn!(error = ?e, "failed to count track numbers"),
+    }
+}
+
+async fn track_once(
+    context: AppContext,
+    track_number: &str,
+    display_name: &str,
+    chat: PackedChat,
+    language: &str,
+) -> anyhow::Result<()> {
     let mut t24client = track24::Client::new();
-    let Ok(response) = t24client.track(track_number).await else {
-        return;
-    };
+    let response = t24client
+        .track(track_number)
+        .await
+        .with_context(|| format!("failed to fetch track number {track_number}"))?;
 
-    let Ok(last_state) = sqlx::query_scalar::<_, Option<String>>(
+    let last_state = sqlx::query_scalar::<_, Option<String>>(
         "SELECT last_state FROM track_numbers WHERE chat_id = ? AND track_number = ?",
     )
     .bind(chat.id)
     .bind(track_number)
     .fetch_one(&context.state.read().await.track_db)
-    .await
-    else {
-        return;
-    };
+    .await?;
     let last_state = if let Some(last_state) = last_state {
-        let Ok(last_state) = serde_json::from_str::<track24::TrackResponse>(&last_state) else {
-            return;
-        };
-        last_state
+        serde_json::from_str::<track24::TrackResponse>(&last_state)?
     } else {
         TrackResponse {
             data: TrackResponseInner { events: vec![] },
@@ -55,7 +134,7 @@ async fn track_once(context: AppContext, track_number: &str, chat: PackedChat) {
         .collect();
 
     if diff.is_empty() {
-        return;
+        return Ok(());
     }
 
     let events = diff
@@ -73,152 +152,265 @@ async fn track_once(context: AppContext, track_number: &str, chat: PackedChat) {
         .collect::<Vec<_>>()
         .join("  \n");
 
-    let Ok(data) = serde_json::to_string(&response) else {
-        return;
-    };
-
-    let Ok(_) = sqlx::query(
-        "UPDATE track_numbers SET last_state = ? WHERE chat_id = ? AND track_number = ?",
-    )
-    .bind(data)
-    .bind(chat.id)
-    .bind(track_number)
-    .execute(&context.state.read().await.track_db)
-    .await
-    else {
-        return;
-    };
+    sqlx::query("UPDATE track_numbers SET last_state = ? WHERE chat_id = ? AND track_number = ?")
+        .bind(serde_json::to_string(&response)?)
+        .bind(chat.id)
+        .bind(track_number)
+        .execute(&context.state.read().await.track_db)
+        .await?;
 
-    let Ok(_) = context
+    context
         .client
         .send_message(
             chat,
-            InputMessage::markdown(format!(
-                "**🔄 Новые обновления для трек номера {track_number}:**  \n{events}"
+            InputMessage::markdown(context.locales().text(
+                language,
+                "track.updates",
+                &[
+                    ("name", escape_markdown(display_name).into()),
+                    ("events", events.into()),
+                ],
             )),
         )
-        .await
+        .await?;
+
+    Ok(())
+}
+
+async fn add_track(
+    context: AppContext,
+    message: &Message,
+    track_number: String,
+    label: Option<String>,
+) -> anyhow::Result<()> {
+    let Ok(entries) = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM track_numbers WHERE chat_id = ? AND track_number = ?",
+    )
+    .bind(message.chat().id())
+    .bind(&track_number)
+    .fetch_one(&context.state.read().await.track_db)
+    .await
     else {
-        return;
+        message
+            .reply(context.text("track.fetch_failed", &[]))
+            .await?;
+        return Ok(());
     };
+
+    if entries > 0 {
+        message
+            .reply(context.text("track.already_added", &[]))
+            .await?;
+        return Ok(());
+    }
+
+    if sqlx::query(
+        "INSERT INTO track_numbers (chat_id, packed, track_number, last_state, label) VALUES (?, ?, ?, NULL, ?)",
+    )
+    .bind(message.chat().id())
+    .bind(&message.chat().pack().to_bytes()[..])
+    .bind(&track_number)
+    .bind(&label)
+    .fetch_all(&context.state.read().await.track_db)
+    .await
+    .is_err()
+    {
+        message.reply(context.text("track.add_failed", &[])).await?;
+        return Ok(());
+    };
+    update_tracked_parcels(context.metrics(), &context.state.read().await.track_db).await;
+
+    message.reply(context.text("track.added", &[])).await?;
+
+    // The number is polled by the scheduled job anyway, so a failed first poll is only logged
+    let language = context.language().to_owned();
+    if let Err(e) = track_once(
+        context,
+        &track_number,
+        &display_name(&track_number, label.as_deref()),
+        message.chat().pack(),
+        &language,
+    )
+    .await
+    {
+        tracing::warn!(%track_number, error = ?e, "failed to track");
+    }
+    Ok(())
 }
 
-pub fn register(
-    mut app: MystbotCore<AppState>,
-    scheduler: &mut AsyncScheduler,
-) -> MystbotCore<AppState> {
-    app.add_command("track", |context, message| Box::pin(async move {
-        let args: Vec<&str> = message.text().split(" ").skip(1).collect();
-        if args.is_empty() {
-            message.reply("Использование: /track (номер трек кода)").await.unwrap();
-            return;
-        }
+async fn track(context: AppContext, message: Message, args: TrackArgs) -> anyhow::Result<()> {
+    if let Some(track_number) = args.track_number {
+        return add_track(context, &message, track_number, args.label).await;
+    }
 
-        let Ok(entries) = sqlx::query_scalar::<_, i64>(
-            "SELECT COUNT(*) FROM track_numbers WHERE chat_id = ? AND track_number = ?",
-        )
-        .bind(message.chat().id())
-        .bind(args[0])
-        .fetch_one(&context.state.read().await.track_db)
-        .await else {
-            message.reply("Не удалось получить информацию о трек кодах").await.unwrap();
-            return;
-        };
+    let Some(key) = ConversationKey::from_message(&message) else {
+        return Ok(());
+    };
+    context
+        .conversations()
+        .set(key, ConversationState::new("track", "track_number"))
+        .await?;
+    message.reply(context.text("track.ask_number", &[])).await?;
+    Ok(())
+}
 
-        if entries > 0 {
-            message.reply("Данный трек код уже добавлен").await.unwrap();
-            return;
+async fn track_conversation(
+    context: AppContext,
+    reply: Reply,
+    mut state: ConversationState,
+) -> anyhow::Result<()> {
+    let Reply::Message(message) = reply else {
+        return Ok(());
+    };
+    let Some(key) = ConversationKey::from_message(&message) else {
+        return Ok(());
+    };
+    let text = message.text().trim().to_owned();
+    if text.is_empty() {
+        return Ok(());
+    }
+
+    match state.step.as_str() {
+        "track_number" => {
+            state.step = "label".to_owned();
+            state.data = text;
+            context.conversations().set(key, state).await?;
+            message.reply(context.text("track.ask_label", &[])).await?;
+            Ok(())
+        }
+        "label" => {
+            context.conversations().finish(key).await?;
+            let label = (text != "-").then_some(text);
+            add_track(context, &message, state.data, label).await
         }
+        _ => {
+            context.conversations().finish(key).await?;
+            Ok(())
+        }
+    }
+}
 
-        if sqlx::query(
-            "INSERT INTO track_numbers (chat_id, packed, track_number, last_state) VALUES (?, ?, ?, NULL)",
-        )
+async fn untrack(
+    context: AppContext,
+    message: Message,
+    args: TrackNumberArgs,
+) -> anyhow::Result<()> {
+    if sqlx::query("DELETE FROM track_numbers WHERE chat_id = ? AND track_number = ?")
         .bind(message.chat().id())
-        .bind(&message.chat().pack().to_bytes()[..])
-        .bind(args[0])
-        .fetch_all(&context.state.read().await.track_db)
-        .await.is_err() {
-            message.reply("Не удалось добавить трек код").await.unwrap();
-            return;
-        };
+        .bind(&args.track_number)
+        .execute(&context.state.read().await.track_db)
+        .await
+        .is_err()
+    {
+        message
+            .reply(context.text("track.remove_failed", &[]))
+            .await?;
+        return Ok(());
+    }
+    update_tracked_parcels(context.metrics(), &context.state.read().await.track_db).await;
+
+    message.reply(context.text("track.removed", &[])).await?;
+    Ok(())
+}
 
-        message.reply("Трек код был успешно добавлен").await.unwrap();
+async fn tracklist(context: AppContext, message: Message) -> anyhow::Result<()> {
+    let Ok(entries) =
+        sqlx::query_as::<_, TrackEntry>("SELECT * FROM track_numbers WHERE chat_id = ?")
+            .bind(message.chat().id())
+            .fetch_all(&context.state.read().await.track_db)
+            .await
+    else {
+        message
+            .reply(context.text("track.fetch_failed", &[]))
+            .await?;
+        return Ok(());
+    };
 
-        track_once(context, args[0], message.chat().pack()).await;
-    }));
+    let count = entries.len();
+    let track_numbers = entries
+        .into_iter()
+        .map(|v| match &v.label {
+            Some(label) => format!("• `{}` {}  ", v.track_number, escape_markdown(label)),
+            None => format!("• `{}`  ", v.track_number),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
 
-    app.add_command("untrack", |context, message| {
-        Box::pin(async move {
-            let args: Vec<&str> = message.text().split(" ").skip(1).collect();
-            if args.is_empty() {
-                message
-                    .reply("Использование: /untrack (номер трек кода)")
-                    .await
-                    .unwrap();
-                return;
-            }
+    message
+        .reply(InputMessage::markdown(context.text(
+            "track.list",
+            &[
+                ("count", count.into()),
+                ("track_numbers", track_numbers.into()),
+            ],
+        )))
+        .await?;
+    Ok(())
+}
 
-            if sqlx::query("DELETE FROM track_numbers WHERE chat_id = ? AND track_number = ?")
-                .bind(message.chat().id())
-                .bind(args[0])
-                .execute(&context.state.read().await.track_db)
-                .await
-                .is_err()
-            {
-                message.reply("Не удалось удалить трек код").await.unwrap();
-                return;
-            }
+async fn track_all(context: AppContext) -> anyhow::Result<()> {
+    let results = sqlx::query_as::<_, TrackEntry>("SELECT * FROM track_numbers")
+        .fetch_all(&context.state.read().await.track_db)
+        .await?;
+    context
+        .metrics()
+        .set("mystbot_tracked_parcels", &[], results.len() as f64);
 
-            message.reply("Трек код был успешно удалён").await.unwrap();
-        })
-    });
-
-    app.add_command("tracklist", |context, message| {
-        Box::pin(async move {
-            let Ok(entries) =
-                sqlx::query_as::<_, TrackEntry>("SELECT * FROM track_numbers WHERE chat_id = ?")
-                    .bind(message.chat().id())
-                    .fetch_all(&context.state.read().await.track_db)
-                    .await
-            else {
-                message
-                    .reply("Не удалось получить информацию о трек кодах")
-                    .await
-                    .unwrap();
-                return;
-            };
-
-            let track_numbers = entries
-                .into_iter()
-                .map(|v| format!("• `{}`  ", v.track_number))
-                .collect::<Vec<_>>()
-                .join("\n");
-
-            message
-                .reply(InputMessage::markdown(format!(
-                    "**Список активных трек кодов:**  \n{track_numbers}"
-                )))
-                .await
-                .unwrap();
-        })
-    });
-
-    app.schedule_every(scheduler, Interval::Minutes(10), |context| {
-        Box::pin(async move {
-            let Ok(results) = sqlx::query_as::<_, TrackEntry>("SELECT * FROM track_numbers")
-                .fetch_all(&context.state.read().await.track_db)
-                .await
-            else {
-                return;
-            };
-            for entry in results {
-                let Ok(chat) = PackedChat::from_bytes(&entry.packed) else {
-                    continue;
-                };
-                track_once(context.clone(), &entry.track_number, chat).await;
-            }
-        })
-    });
+    let mut result = Ok(());
+    for entry in results {
+        let Ok(chat) = PackedChat::from_bytes(&entry.packed) else {
+            continue;
+        };
+        // Private chats have the id of the user, other chats get the default language
+        let language = context.locales().language(chat.id, None).await;
+        if let Err(e) = track_once(
+            context.clone(),
+            &entry.track_number,
+            &display_name(&entry.track_number, entry.label.as_deref()),
+            chat,
+            &language,
+        )
+        .await
+        {
+            tracing::warn!(track_number = %entry.track_number, error = ?e, "failed to track");
+            result = Err(e);
+        }
+    }
+    result
+}
+
+async fn bot_membership(context: AppContext, update: MemberUpdate) -> anyhow::Result<()> {
+    if update.change != MemberChange::Left {
+        return Ok(());
+    }
+
+    let state = context.state.read().await;
+    sqlx::query("DELETE FROM track_numbers WHERE chat_id = ?")
+        .bind(update.chat_id)
+        .execute(&state.track_db)
+        .await?;
+    update_tracked_parcels(context.metrics(), &state.track_db).await;
+    Ok(())
+}
+
+pub fn register(mut app: MystbotCore<AppState>) -> MystbotCore<AppState> {
+    app.set_module("modules.track");
+    app.add_command_with_args("track", track)
+        .description("commands.track")
+        .alias("t")
+        .handle_edits();
+    app.add_conversation("track", track_conversation);
+    app.add_command_with_args("untrack", untrack)
+        .description("commands.untrack");
+    app.add_command("tracklist", tracklist)
+        .description("commands.tracklist");
+    app.add_bot_membership(bot_membership);
+    app.add_job(
+        "track_all",
+        Schedule::every(Duration::from_secs(600)),
+        track_all,
+    )
+    .jitter(Duration::from_secs(30));
 
     app
 }
@@ -0,0 +1,274 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ArgKind {
+    Required,
+    Optional,
+    Rest,
+}
+
+#[derive(Clone)]
+struct Arg {
+    name: String,
+    description: String,
+    kind: ArgKind,
+}
+
+/// ## ArgSpec
+/// Description of command arguments, used for parsing and for generating usage messages.
+/// Argument descriptions are message keys translated to the language of the user
+#[derive(Clone, Default)]
+pub struct ArgSpec {
+    args: Vec<Arg>,
+}
+
+impl ArgSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(
+        mut self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        kind: ArgKind,
+    ) -> Self {
+        assert!(
+            self.args.last().is_none_or(|a| a.kind != ArgKind::Rest),
+            "rest of line argument must be the last one"
+        );
+        assert!(
+            kind != ArgKind::Required
+                || self.args.last().is_none_or(|a| a.kind == ArgKind::Required),
+            "required argument cannot follow an optional one"
+        );
+        self.args.push(Arg {
+            name: name.into(),
+            description: description.into(),
+            kind,
+        });
+        self
+    }
+
+    /// Add positional argument that must be present
+    pub fn required(self, name: impl Into<String>, description: impl Into<String>) -> Self {
+        self.push(name, description, ArgKind::Required)
+    }
+
+    /// Add positional argument that can be omitted
+    pub fn optional(self, name: impl Into<String>, description: impl Into<String>) -> Self {
+        self.push(name, description, ArgKind::Optional)
+    }
+
+    /// Add argument that takes the rest of the line as is, can be omitted and must be the last one
+    pub fn rest(self, name: impl Into<String>, description: impl Into<String>) -> Self {
+        self.push(name, description, ArgKind::Rest)
+    }
+
+    /// Usage line for `command` with argument descriptions passed through `text`, e.g. `/track (code) [label]`
+    pub fn usage(&self, command: &str, text: impl Fn(&str) -> String) -> String {
+        let mut usage = format!("/{command}");
+        for arg in self.args.iter() {
+            usage.push(' ');
+            usage.push_str(&match arg.kind {
+                ArgKind::Required => format!("({})", text(&arg.description)),
+                ArgKind::Optional => format!("[{}]", text(&arg.description)),
+                ArgKind::Rest => format!("[{}...]", text(&arg.description)),
+            });
+        }
+        usage
+    }
+
+    /// Parse arguments from the message text, the first word (the command itself) is skipped
+    pub fn parse(&self, text: &str) -> Result<Args, ArgsError> {
+        let mut input = text.trim_start();
+        input = input
+            .find(char::is_whitespace)
+            .map(move |i| &input[i..])
+            .unwrap_or_default();
+
+        let mut values = HashMap::new();
+        for arg in self.args.iter() {
+            let value = if arg.kind == ArgKind::Rest {
+                let rest = input.trim();
+                input = "";
+                (!rest.is_empty()).then(|| rest.to_owned())
+            } else {
+                next_word(&mut input)?
+            };
+
+            match value {
+                Some(value) => {
+                    values.insert(arg.name.clone(), value);
+                }
+                None if arg.kind == ArgKind::Required => {
+                    return Err(ArgsError::Missing(arg.name.clone()));
+                }
+                None => {}
+            }
+        }
+
+        if !input.trim().is_empty() {
+            return Err(ArgsError::TooMany);
+        }
+
+        Ok(Args { values })
+    }
+}
+
+fn next_word(input: &mut &str) -> Result<Option<String>, ArgsError> {
+    let text = input.trim_start();
+    let Some(first) = text.chars().next() else {
+        *input = text;
+        return Ok(None);
+    };
+
+    if first != '"' {
+        let end = text.find(char::is_whitespace).unwrap_or(text.len());
+        *input = &text[end..];
+        return Ok(Some(text[..end].to_owned()));
+    }
+
+    let mut word = String::new();
+    let mut chars = text.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    word.push(escaped);
+                }
+            }
+            '"' => {
+                *input = &text[i + 1..];
+                return Ok(Some(word));
+            }
+            c => word.push(c),
+        }
+    }
+
+    Err(ArgsError::UnclosedQuote)
+}
+
+/// ## Args
+/// Parsed command arguments
+pub struct Args {
+    values: HashMap<String, String>,
+}
+
+impl Args {
+    /// Raw value of the argument
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|s| s.as_str())
+    }
+
+    /// Take and convert the argument that has to be present
+    pub fn take<T: FromStr>(&mut self, name: &str) -> Result<T, ArgsError> {
+        self.take_optional(name)?
+            .ok_or_else(|| ArgsError::Missing(name.to_owned()))
+    }
+
+    /// Take and convert the argument that can be omitted
+    pub fn take_optional<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, ArgsError> {
+        self.values
+            .remove(name)
+            .map(|v| v.parse().map_err(|_| ArgsError::Invalid(name.to_owned())))
+            .transpose()
+    }
+}
+
+/// ## ArgsError
+/// Reason why arguments could not be parsed
+#[derive(Debug)]
+pub enum ArgsError {
+    Missing(String),
+    Invalid(String),
+    TooMany,
+    UnclosedQuote,
+}
+
+impl Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::Missing(name) => write!(f, "missing argument `{name}`"),
+            ArgsError::Invalid(name) => write!(f, "invalid argument `{name}`"),
+            ArgsError::TooMany => write!(f, "too many arguments"),
+            ArgsError::UnclosedQuote => write!(f, "unclosed quote"),
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+/// ## FromArgs
+/// Command arguments that can be parsed from a message
+pub trait FromArgs: Sized {
+    fn spec() -> ArgSpec;
+    fn from_args(args: Args) -> Result<Self, ArgsError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> ArgSpec {
+        ArgSpec::new()
+            .required("code", "code")
+            .optional("count", "count")
+            .rest("label", "label")
+    }
+
+    #[test]
+    fn quoted() {
+        let args = spec()
+            .parse(r#"/track "AB 12" 3 my "big" parcel "#)
+            .unwrap();
+        assert_eq!(args.get("code"), Some("AB 12"));
+        assert_eq!(args.get("count"), Some("3"));
+        assert_eq!(args.get("label"), Some(r#"my "big" parcel"#));
+
+        let args = spec().parse(r#"/track "a \"b\" \\c""#).unwrap();
+        assert_eq!(args.get("code"), Some(r#"a "b" \c"#));
+        assert_eq!(args.get("count"), None);
+        assert_eq!(args.get("label"), None);
+
+        assert!(matches!(
+            spec().parse(r#"/track "AB 12"#),
+            Err(ArgsError::UnclosedQuote)
+        ));
+    }
+
+    #[test]
+    fn errors() {
+        let spec = ArgSpec::new().required("user_id", "user id");
+        assert!(matches!(spec.parse("/revoke 1 2"), Err(ArgsError::TooMany)));
+        assert!(
+            matches!(spec.parse("/revoke"), Err(ArgsError::Missing(name)) if name == "user_id")
+        );
+        assert!(matches!(
+            spec.parse("/revoke  "),
+            Err(ArgsError::Missing(_))
+        ));
+
+        let mut args = spec.parse("/revoke x").unwrap();
+        assert!(matches!(
+            args.take::<i64>("user_id"),
+            Err(ArgsError::Invalid(_))
+        ));
+        assert!(matches!(
+            args.take::<i64>("user_id"),
+            Err(ArgsError::Missing(_))
+        ));
+    }
+
+    #[test]
+    fn usage() {
+        assert_eq!(
+            spec().usage("track", |key| key.to_uppercase()),
+            "/track (CODE) [COUNT] [LABEL...]"
+        );
+        assert_eq!(ArgSpec::new().usage("help", str::to_owned), "/help");
+    }
+}
@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+/// Separator between the handler prefix and the payload in callback data
+pub const SEPARATOR: u8 = b'|';
+
+/// Maximum length of callback data accepted by Telegram
+pub const MAX_DATA_LEN: usize = 64;
+
+/// ## CallbackPayload
+/// Payload that can be stored inside of callback data
+pub trait CallbackPayload: Sized {
+    fn encode(&self) -> Vec<u8>;
+    fn decode(data: &[u8]) -> Option<Self>;
+}
+
+impl CallbackPayload for () {
+    fn encode(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    fn decode(_: &[u8]) -> Option<Self> {
+        Some(())
+    }
+}
+
+/// Build callback data for the handler registered with `prefix`, fails if the prefix contains `SEPARATOR`
+/// or the data does not fit into `MAX_DATA_LEN` bytes
+pub fn encode<P: CallbackPayload>(prefix: &str, payload: &P) -> anyhow::Result<Vec<u8>> {
+    anyhow::ensure!(
+        !prefix.as_bytes().contains(&SEPARATOR),
+        "callback prefix `{prefix}` contains the separator"
+    );
+    let mut data = prefix.as_bytes().to_vec();
+    data.push(SEPARATOR);
+    data.extend(payload.encode());
+    anyhow::ensure!(
+        data.len() <= MAX_DATA_LEN,
+        "callback data for `{prefix}` is longer than {MAX_DATA_LEN} bytes"
+    );
+    Ok(data)
+}
+
+/// Split callback data into handler prefix and raw payload
+pub fn split(data: &[u8]) -> Option<(&str, &[u8])> {
+    let position = data.iter().position(|b| *b == SEPARATOR)?;
+    Some((
+        std::str::from_utf8(&data[..position]).ok()?,
+        &data[position + 1..],
+    ))
+}
+
+/// Decode payload from callback data
+pub fn decode<P: CallbackPayload>(data: &[u8]) -> Option<P> {
+    let (_, payload) = split(data)?;
+    P::decode(payload)
+}
+
+/// ## PayloadField
+/// Value stored in callback data, numbers are stored as varints to keep callback data short.
+/// Every field is a payload on its own and a field of enums declared with `callback_payload!`
+pub trait PayloadField: Sized {
+    fn write(&self, out: &mut Vec<u8>);
+    fn read(data: &mut &[u8]) -> Option<Self>;
+}
+
+fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn read_varint(data: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = data.split_first()?;
+        *data = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+macro_rules! impl_unsigned_field {
+    ($($t:ty),*) => {
+        $(
+            impl PayloadField for $t {
+                fn write(&self, out: &mut Vec<u8>) {
+                    write_varint(u64::from(*self), out);
+                }
+
+                fn read(data: &mut &[u8]) -> Option<Self> {
+                    Self::try_from(read_varint(data)?).ok()
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_signed_field {
+    ($($t:ty),*) => {
+        $(
+            impl PayloadField for $t {
+                fn write(&self, out: &mut Vec<u8>) {
+                    let value = i64::from(*self);
+                    write_varint(((value << 1) ^ (value >> 63)) as u64, out);
+                }
+
+                fn read(data: &mut &[u8]) -> Option<Self> {
+                    let value = read_varint(data)?;
+                    Self::try_from((value >> 1) as i64 ^ -((value & 1) as i64)).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned_field!(u8, u16, u32, u64);
+impl_signed_field!(i8, i16, i32, i64);
+
+impl PayloadField for bool {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+
+    fn read(data: &mut &[u8]) -> Option<Self> {
+        match u8::read(data)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl PayloadField for Vec<u8> {
+    fn write(&self, out: &mut Vec<u8>) {
+        write_varint(self.len() as u64, out);
+        out.extend(self);
+    }
+
+    fn read(data: &mut &[u8]) -> Option<Self> {
+        let len = usize::try_from(read_varint(data)?).ok()?;
+        if data.len() < len {
+            return None;
+        }
+        let (value, rest) = data.split_at(len);
+        *data = rest;
+        Some(value.to_vec())
+    }
+}
+
+impl PayloadField for String {
+    fn write(&self, out: &mut Vec<u8>) {
+        write_varint(self.len() as u64, out);
+        out.extend(self.as_bytes());
+    }
+
+    fn read(data: &mut &[u8]) -> Option<Self> {
+        String::from_utf8(Vec::read(data)?).ok()
+    }
+}
+
+macro_rules! impl_field_payload {
+    ($($t:ty),*) => {
+        $(
+            impl CallbackPayload for $t {
+                fn encode(&self) -> Vec<u8> {
+                    let mut out = vec![];
+                    self.write(&mut out);
+                    out
+                }
+
+                fn decode(mut data: &[u8]) -> Option<Self> {
+                    let value = Self::read(&mut data)?;
+                    data.is_empty().then_some(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_field_payload!(u8, u16, u32, u64, i8, i16, i32, i64, bool, String, Vec<u8>);
+
+/// Declare an enum of button payloads with compact versioned encoding, the first byte is the version and the second one is the variant tag.
+/// Bump the version when the meaning of tags or fields changes, data of other versions fails to decode
+///
+/// ```ignore
+/// callback_payload! {
+///     pub enum TrackButton: 1 {
+///         Refresh = 0,
+///         Remove { id: i64 } = 1,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! callback_payload {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident : $version:literal {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident $({ $($field:ident : $ty:ty),* $(,)? })? = $tag:literal
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant $({ $($field: $ty),* })?
+            ),*
+        }
+
+        impl $crate::callback::CallbackPayload for $name {
+            fn encode(&self) -> Vec<u8> {
+                let mut out = vec![$version];
+                match self {
+                    $(
+                        Self::$variant $({ $($field),* })? => {
+                            out.push($tag);
+                            $($($crate::callback::PayloadField::write($field, &mut out);)*)?
+                        }
+                    )*
+                }
+                out
+            }
+
+            fn decode(data: &[u8]) -> Option<Self> {
+                let (&version, data) = data.split_first()?;
+                if version != $version {
+                    return None;
+                }
+                let (&tag, mut data) = data.split_first()?;
+                let value = match tag {
+                    $(
+                        $tag => Self::$variant $({
+                            $($field: <$ty as $crate::callback::PayloadField>::read(&mut data)?),*
+                        })?,
+                    )*
+                    _ => return None,
+                };
+                data.is_empty().then_some(value)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<P: CallbackPayload + PartialEq + std::fmt::Debug>(payload: P) {
+        let data = encode("test", &payload).unwrap();
+        assert_eq!(decode::<P>(&data), Some(payload));
+    }
+
+    #[test]
+    fn plain_values_use_field_encoding() {
+        round_trip(());
+        round_trip(300u32);
+        round_trip(-1i64);
+        round_trip(i64::MIN);
+        round_trip(true);
+        round_trip("трек".to_owned());
+        round_trip(vec![0u8, 255]);
+
+        assert_eq!(300u32.encode(), [0xac, 0x02]);
+        assert_eq!((-1i32).encode(), [0x01]);
+        assert_eq!(u32::decode(&[0xac, 0x02, 0x00]), None);
+        assert_eq!(u8::decode(&[0xac, 0x02]), None);
+    }
+
+    callback_payload! {
+        #[derive(PartialEq, Debug)]
+        enum Button: 1 {
+            Refresh = 0,
+            Remove { id: i64, label: String } = 1,
+        }
+    }
+
+    #[test]
+    fn enums() {
+        round_trip(Button::Refresh);
+        round_trip(Button::Remove {
+            id: -5,
+            label: "label".to_owned(),
+        });
+        assert_eq!(
+            Button::Remove {
+                id: 150,
+                label: String::new()
+            }
+            .encode(),
+            [1, 1, 0xac, 0x02, 0]
+        );
+        assert_eq!(Button::decode(&[2, 0]), None);
+        assert_eq!(Button::decode(&[1, 2]), None);
+        assert_eq!(Button::decode(&[1, 0, 0]), None);
+    }
+
+    #[test]
+    fn limits() {
+        assert!(encode("a|b", &()).is_err());
+        assert!(encode("test", &"x".repeat(MAX_DATA_LEN)).is_err());
+        assert_eq!(split(b"test|\x01"), Some(("test", &b"\x01"[..])));
+    }
+}
@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use grammers_client::{Client, InvocationError, grammers_tl_types, types::CallbackQuery};
+use std::time::Duration;
+
+/// ## CallbackAnswer
+/// Answer to a button press, Telegram clients keep showing a loading indicator until the query is answered
+#[derive(Clone, Default)]
+pub struct CallbackAnswer {
+    pub(crate) message: Option<String>,
+    pub(crate) alert: bool,
+    url: Option<String>,
+    cache_time: Duration,
+}
+
+impl CallbackAnswer {
+    /// Only stop the loading indicator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Text shown at the top of the chat for a few seconds
+    pub fn toast(text: impl Into<String>) -> Self {
+        Self {
+            message: Some(text.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Text shown in a dialog the user has to close
+    pub fn alert(text: impl Into<String>) -> Self {
+        Self {
+            message: Some(text.into()),
+            alert: true,
+            ..Default::default()
+        }
+    }
+
+    /// Open `url`, only `t.me` links to the bot with a start parameter and game URLs are allowed
+    pub fn url(url: impl Into<String>) -> Self {
+        Self {
+            url: Some(url.into()),
+            ..Default::default()
+        }
+    }
+
+    /// How long clients may reuse this answer for presses of the same button
+    pub const fn cache_time(mut self, cache_time: Duration) -> Self {
+        self.cache_time = cache_time;
+        self
+    }
+}
+
+pub trait CallbackQueryExt {
+    fn answer_callback_query(
+        &self,
+        query: &CallbackQuery,
+        answer: CallbackAnswer,
+    ) -> impl Future<Output = Result<bool, InvocationError>> + Send;
+}
+
+impl CallbackQueryExt for Client {
+    async fn answer_callback_query(
+        &self,
+        query: &CallbackQuery,
+        answer: CallbackAnswer,
+    ) -> Result<bool, InvocationError> {
+        self.invoke(
+            &grammers_tl_types::functions::messages::SetBotCallbackAnswer {
+                alert: answer.alert,
+                query_id: query.query_id(),
+                message: answer.message,
+                url: answer.url,
+                cache_time: answer.cache_time.as_secs() as i32,
+            },
+        )
+        .await
+    }
+}
@@ -0,0 +1,260 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{
+    callback_query_ext::{CallbackAnswer, CallbackQueryExt},
+    inline_message_ext::{InlineEdit, InlineMessageExt},
+};
+use grammers_client::{
+    Client, InvocationError,
+    grammers_tl_types::{
+        enums::InputBotInlineMessageId,
+        functions::{
+            bots::{ResetBotCommands, SetBotCommands},
+            messages::SetInlineBotResults,
+        },
+    },
+    types::{CallbackQuery, Chat, InlineQuery, InlineSend, Message, PackedChat},
+};
+
+/// ## BotClient
+/// Calls to Telegram made by handlers and `MystbotCore`, implemented for `grammers_client::Client`,
+/// the update types are what handlers receive from this client
+pub trait BotClient: Clone + Send + Sync + 'static {
+    type Message: IncomingMessage;
+    type CallbackQuery: IncomingCallbackQuery;
+    type InlineQuery: IncomingInlineQuery;
+    type InlineSend: IncomingInlineSend;
+
+    /// Reply to `message` in its chat
+    fn reply(
+        &self,
+        message: &Self::Message,
+        text: String,
+    ) -> impl Future<Output = Result<(), InvocationError>> + Send;
+
+    fn send_message(
+        &self,
+        chat: PackedChat,
+        text: String,
+    ) -> impl Future<Output = Result<(), InvocationError>> + Send;
+
+    /// Edit message sent from an inline result
+    fn edit_inline_message(
+        &self,
+        id: InputBotInlineMessageId,
+        edit: InlineEdit,
+    ) -> impl Future<Output = Result<bool, InvocationError>> + Send;
+
+    /// Answer inline query, the request is built by `inline_query::InlineQuery::answer`
+    fn answer(
+        &self,
+        request: SetInlineBotResults,
+    ) -> impl Future<Output = Result<(), InvocationError>> + Send;
+
+    fn answer_callback_query(
+        &self,
+        query: &Self::CallbackQuery,
+        answer: CallbackAnswer,
+    ) -> impl Future<Output = Result<bool, InvocationError>> + Send;
+
+    /// Set the Telegram command menu
+    fn set_bot_commands(
+        &self,
+        request: SetBotCommands,
+    ) -> impl Future<Output = Result<(), InvocationError>> + Send;
+
+    /// Reset the Telegram command menu to the one of the wider scope
+    fn reset_bot_commands(
+        &self,
+        request: ResetBotCommands,
+    ) -> impl Future<Output = Result<(), InvocationError>> + Send;
+}
+
+/// ## IncomingUpdate
+/// What `MystbotCore` reads from updates to pick the language and the role of the sender
+pub trait IncomingUpdate: Clone + Send + Sync + 'static {
+    /// Id of the user or chat that caused the update
+    fn sender_id(&self) -> Option<i64>;
+
+    /// Id of the chat where the update happened, inline queries have no chat
+    fn chat_id(&self) -> Option<i64>;
+
+    /// Language code of the Telegram app of the sender, if Telegram sent it
+    fn lang_code(&self) -> Option<String>;
+}
+
+pub trait IncomingMessage: IncomingUpdate {
+    fn text(&self) -> &str;
+
+    /// Private chat with the sender, `None` for messages in groups and channels
+    fn private_chat(&self) -> Option<PackedChat>;
+}
+
+pub trait IncomingCallbackQuery: IncomingUpdate {
+    fn data(&self) -> &[u8];
+}
+
+pub trait IncomingInlineQuery: IncomingUpdate {
+    fn query_id(&self) -> i64;
+
+    fn text(&self) -> &str;
+
+    /// Offset the user scrolled to, empty for the first page
+    fn offset(&self) -> &str;
+}
+
+pub trait IncomingInlineSend: IncomingUpdate {
+    fn result_id(&self) -> &str;
+
+    /// Id of the sent message, `None` if the result has no keyboard
+    fn message_id(&self) -> Option<InputBotInlineMessageId>;
+}
+
+impl BotClient for Client {
+    type Message = Message;
+    type CallbackQuery = CallbackQuery;
+    type InlineQuery = InlineQuery;
+    type InlineSend = InlineSend;
+
+    async fn reply(&self, message: &Message, text: String) -> Result<(), InvocationError> {
+        message.reply(text).await.map(drop)
+    }
+
+    async fn send_message(&self, chat: PackedChat, text: String) -> Result<(), InvocationError> {
+        Client::send_message(self, chat, text).await.map(drop)
+    }
+
+    async fn edit_inline_message(
+        &self,
+        id: InputBotInlineMessageId,
+        edit: InlineEdit,
+    ) -> Result<bool, InvocationError> {
+        self.edit_inline_message_ext(id, edit).await
+    }
+
+    async fn answer(&self, request: SetInlineBotResults) -> Result<(), InvocationError> {
+        self.invoke(&request).await.map(drop)
+    }
+
+    async fn answer_callback_query(
+        &self,
+        query: &CallbackQuery,
+        answer: CallbackAnswer,
+    ) -> Result<bool, InvocationError> {
+        CallbackQueryExt::answer_callback_query(self, query, answer).await
+    }
+
+    async fn set_bot_commands(&self, request: SetBotCommands) -> Result<(), InvocationError> {
+        self.invoke(&request).await.map(drop)
+    }
+
+    async fn reset_bot_commands(&self, request: ResetBotCommands) -> Result<(), InvocationError> {
+        self.invoke(&request).await.map(drop)
+    }
+}
+
+impl IncomingUpdate for Message {
+    fn sender_id(&self) -> Option<i64> {
+        self.sender().map(|s| s.id())
+    }
+
+    fn chat_id(&self) -> Option<i64> {
+        Some(self.chat().id())
+    }
+
+    fn lang_code(&self) -> Option<String> {
+        match self.sender()? {
+            Chat::User(user) => user.lang_code().map(str::to_owned),
+            _ => None,
+        }
+    }
+}
+
+impl IncomingMessage for Message {
+    fn text(&self) -> &str {
+        Message::text(self)
+    }
+
+    fn private_chat(&self) -> Option<PackedChat> {
+        match self.chat() {
+            Chat::User(user) => Some(user.pack()),
+            _ => None,
+        }
+    }
+}
+
+impl IncomingUpdate for CallbackQuery {
+    fn sender_id(&self) -> Option<i64> {
+        Some(self.sender().id())
+    }
+
+    fn chat_id(&self) -> Option<i64> {
+        Some(self.chat().id())
+    }
+
+    fn lang_code(&self) -> Option<String> {
+        match self.sender() {
+            Chat::User(user) => user.lang_code().map(str::to_owned),
+            _ => None,
+        }
+    }
+}
+
+impl IncomingCallbackQuery for CallbackQuery {
+    fn data(&self) -> &[u8] {
+        CallbackQuery::data(self)
+    }
+}
+
+impl IncomingUpdate for InlineQuery {
+    fn sender_id(&self) -> Option<i64> {
+        Some(self.sender().id())
+    }
+
+    fn chat_id(&self) -> Option<i64> {
+        None
+    }
+
+    fn lang_code(&self) -> Option<String> {
+        self.sender().lang_code().map(str::to_owned)
+    }
+}
+
+impl IncomingInlineQuery for InlineQuery {
+    fn query_id(&self) -> i64 {
+        InlineQuery::query_id(self)
+    }
+
+    fn text(&self) -> &str {
+        InlineQuery::text(self)
+    }
+
+    fn offset(&self) -> &str {
+        InlineQuery::offset(self)
+    }
+}
+
+impl IncomingUpdate for InlineSend {
+    fn sender_id(&self) -> Option<i64> {
+        Some(self.sender().id())
+    }
+
+    fn chat_id(&self) -> Option<i64> {
+        None
+    }
+
+    fn lang_code(&self) -> Option<String> {
+        self.sender().lang_code().map(str::to_owned)
+    }
+}
+
+impl IncomingInlineSend for InlineSend {
+    fn result_id(&self) -> &str {
+        InlineSend::result_id(self)
+    }
+
+    fn message_id(&self) -> Option<InputBotInlineMessageId> {
+        InlineSend::message_id(self)
+    }
+}
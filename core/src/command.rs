@@ -0,0 +1,377 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{args::ArgSpec, client::BotClient, handler::BoxedHandler, role::Role};
+use dashmap::{DashMap, mapref::one::RefMut};
+use grammers_client::{Client, grammers_tl_types, types::PackedChat};
+use std::{fmt::Display, str::FromStr};
+
+/// ## CommandScope
+/// Chats where the command is shown in the Telegram command menu
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CommandScope {
+    All,
+    Private,
+    Group,
+}
+
+/// ## CommandInfo
+/// Description of a registered command used for `/help` and the Telegram command menu,
+/// the description, the module and the usage are message keys translated for every user
+#[derive(Clone)]
+pub struct CommandInfo {
+    pub name: String,
+    /// Set with `CommandOptions::usage`, otherwise the usage line is generated from `args`
+    pub usage: Option<String>,
+    pub args: ArgSpec,
+    pub description: Option<String>,
+    pub module: String,
+    pub scope: CommandScope,
+    /// Minimum role needed to run the command, it is hidden from users below it
+    pub role: Role,
+}
+
+impl CommandInfo {
+    /// Usage line with messages passed through `text`, e.g. `/track (code) [label]`
+    pub fn usage_line(&self, text: impl Fn(&str) -> String) -> String {
+        match &self.usage {
+            Some(usage) => text(usage),
+            None => self.args.usage(&self.name, text),
+        }
+    }
+}
+
+pub(crate) struct CommandData<State, C: BotClient> {
+    pub(crate) func: BoxedHandler<State, (C::Message,), C>,
+    pub(crate) handle_edits: bool,
+    pub(crate) info: CommandInfo,
+}
+
+/// ## CommandOptions
+/// Returned by `add_command`, sets how the command is presented to users
+pub struct CommandOptions<'a, State, C: BotClient = Client> {
+    pub(crate) data: RefMut<'a, String, CommandData<State, C>>,
+    pub(crate) aliases: &'a DashMap<String, String>,
+}
+
+impl<State, C: BotClient> CommandOptions<'_, State, C> {
+    /// Short description or its message key, commands without one are not listed in `/help` and the command menu
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.data.info.description = Some(description.into());
+        self
+    }
+
+    /// Another name the command can be called with, aliases are not shown in `/help` and the command menu
+    pub fn alias(self, alias: impl Into<String>) -> Self {
+        self.aliases
+            .insert(alias.into().to_lowercase(), self.data.info.name.clone());
+        self
+    }
+
+    /// Run the command again when the message with it is edited
+    pub fn handle_edits(mut self) -> Self {
+        self.data.handle_edits = true;
+        self
+    }
+
+    /// Usage line shown in `/help` or its message key, generated from the arguments by default
+    pub fn usage(mut self, usage: impl Into<String>) -> Self {
+        self.data.info.usage = Some(usage.into());
+        self
+    }
+
+    /// Chats where the command is shown in the command menu
+    pub fn scope(mut self, scope: CommandScope) -> Self {
+        self.data.info.scope = scope;
+        self
+    }
+
+    /// Minimum role needed to run the command, `Role::User` by default
+    pub fn role(mut self, role: Role) -> Self {
+        self.data.info.role = role;
+        self
+    }
+}
+
+/// Split `/name@bot args` into the command name and the bot username, `None` if the text is not a command
+pub(crate) fn parse(text: &str) -> Option<(&str, Option<&str>)> {
+    let command = text.strip_prefix('/')?.split(char::is_whitespace).next()?;
+    let (name, username) = match command.split_once('@') {
+        Some((name, username)) => (name, Some(username)),
+        None => (command, None),
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some((name, username))
+}
+
+/// Build `/help` text from the commands, grouped by module in registration order, messages are passed through `text`
+pub(crate) fn help_text<'a>(
+    header: &str,
+    commands: impl IntoIterator<Item = &'a CommandInfo>,
+    text: impl Fn(&str) -> String,
+) -> String {
+    let mut modules: Vec<(&str, Vec<&CommandInfo>)> = vec![];
+    for info in commands {
+        if info.description.is_none() {
+            continue;
+        }
+        match modules.iter_mut().find(|(m, _)| *m == info.module) {
+            Some((_, infos)) => infos.push(info),
+            None => modules.push((&info.module, vec![info])),
+        }
+    }
+
+    let mut help = text(header);
+    for (module, infos) in modules {
+        help.push_str(&format!("\n\n{}", text(module)));
+        for info in infos {
+            help.push_str(&format!(
+                "\n{} - {}",
+                info.usage_line(&text),
+                text(info.description.as_deref().unwrap_or_default())
+            ));
+        }
+    }
+    help
+}
+
+/// Build `bots.setBotCommands` request for the chats of `scope` and users with `lang_code`, empty for everyone else.
+/// Only commands that every user can run are listed, descriptions are passed through `text`
+pub(crate) fn set_bot_commands<'a>(
+    scope: CommandScope,
+    lang_code: &str,
+    commands: impl IntoIterator<Item = &'a CommandInfo>,
+    text: impl Fn(&str) -> String,
+) -> grammers_tl_types::functions::bots::SetBotCommands {
+    grammers_tl_types::functions::bots::SetBotCommands {
+        scope: match scope {
+            CommandScope::All => grammers_tl_types::enums::BotCommandScope::Default,
+            CommandScope::Private => grammers_tl_types::enums::BotCommandScope::Users,
+            CommandScope::Group => grammers_tl_types::enums::BotCommandScope::Chats,
+        },
+        lang_code: lang_code.to_owned(),
+        commands: bot_commands(scope, Role::User, commands, text),
+    }
+}
+
+/// ## PeerMenu
+/// Command menu published for the private chat with an owner or admin, kept so it can be updated or reset after a restart
+#[derive(Clone, PartialEq, Debug)]
+pub struct PeerMenu {
+    pub role: Role,
+    pub language: String,
+    pub peer: PackedChat,
+}
+
+impl Display for PeerMenu {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} ", self.role, self.language)?;
+        for byte in self.peer.to_bytes() {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PeerMenu {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(' ');
+        let (Some(role), Some(language), Some(peer), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            anyhow::bail!("invalid peer menu {s}");
+        };
+        let peer = (0..peer.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(peer.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<_>>>()
+            .and_then(|bytes| PackedChat::from_bytes(&bytes).ok())
+            .ok_or_else(|| anyhow::anyhow!("invalid peer in peer menu {s}"))?;
+        Ok(Self {
+            role: role.parse()?,
+            language: language.to_owned(),
+            peer,
+        })
+    }
+}
+
+/// Build `bots.setBotCommands` request for the private chat with `peer`, listing the commands that `role` can run
+pub(crate) fn set_peer_commands<'a>(
+    peer: grammers_tl_types::enums::InputPeer,
+    role: Role,
+    commands: impl IntoIterator<Item = &'a CommandInfo>,
+    text: impl Fn(&str) -> String,
+) -> grammers_tl_types::functions::bots::SetBotCommands {
+    grammers_tl_types::functions::bots::SetBotCommands {
+        scope: grammers_tl_types::enums::BotCommandScope::Peer(
+            grammers_tl_types::types::BotCommandScopePeer { peer },
+        ),
+        lang_code: String::new(),
+        commands: bot_commands(CommandScope::Private, role, commands, text),
+    }
+}
+
+/// Build `bots.resetBotCommands` request for the private chat with `peer`, it gets the menu of everyone else again
+pub(crate) fn reset_peer_commands(
+    peer: grammers_tl_types::enums::InputPeer,
+) -> grammers_tl_types::functions::bots::ResetBotCommands {
+    grammers_tl_types::functions::bots::ResetBotCommands {
+        scope: grammers_tl_types::enums::BotCommandScope::Peer(
+            grammers_tl_types::types::BotCommandScopePeer { peer },
+        ),
+        lang_code: String::new(),
+    }
+}
+
+fn bot_commands<'a>(
+    scope: CommandScope,
+    role: Role,
+    commands: impl IntoIterator<Item = &'a CommandInfo>,
+    text: impl Fn(&str) -> String,
+) -> Vec<grammers_tl_types::enums::BotCommand> {
+    commands
+        .into_iter()
+        .filter(|info| info.scope == CommandScope::All || info.scope == scope)
+        .filter(|info| info.role <= role)
+        .filter_map(|info| {
+            Some(grammers_tl_types::enums::BotCommand::Command(
+                grammers_tl_types::types::BotCommand {
+                    command: info.name.clone(),
+                    description: text(info.description.as_deref()?),
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grammers_client::{grammers_tl_types::enums, session::PackedType};
+
+    fn info(name: &str, module: &str, description: Option<&str>) -> CommandInfo {
+        CommandInfo {
+            name: name.to_owned(),
+            usage: None,
+            args: ArgSpec::new(),
+            description: description.map(str::to_owned),
+            module: module.to_owned(),
+            scope: CommandScope::All,
+            role: Role::User,
+        }
+    }
+
+    fn commands() -> Vec<CommandInfo> {
+        let mut track = info("track", "modules.track", Some("commands.track"));
+        track.args = ArgSpec::new().required("code", "args.code");
+        let mut start = info("start", "modules.general", Some("commands.start"));
+        start.scope = CommandScope::Private;
+        let mut grant = info("grant", "modules.admin", Some("commands.grant"));
+        grant.role = Role::Admin;
+        let mut tracklist = info("tracklist", "modules.track", Some("commands.tracklist"));
+        tracklist.usage = Some("usage.tracklist".to_owned());
+        vec![
+            track,
+            start,
+            info("hidden", "modules.general", None),
+            grant,
+            tracklist,
+        ]
+    }
+
+    fn names(request: &grammers_tl_types::functions::bots::SetBotCommands) -> Vec<String> {
+        request
+            .commands
+            .iter()
+            .map(|command| match command {
+                enums::BotCommand::Command(command) => {
+                    format!("{} {}", command.command, command.description)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(parse("/start"), Some(("start", None)));
+        assert_eq!(
+            parse("/Track@MystBot  AB12"),
+            Some(("Track", Some("MystBot")))
+        );
+        assert_eq!(
+            parse("/track_list\nsecond line"),
+            Some(("track_list", None))
+        );
+        assert_eq!(parse("/start@"), Some(("start", Some(""))));
+        assert_eq!(parse("start"), None);
+        assert_eq!(parse("/"), None);
+        assert_eq!(parse("/ start"), None);
+        assert_eq!(parse("/трек"), None);
+        assert_eq!(parse("/@MystBot"), None);
+    }
+
+    #[test]
+    fn peer_menus() {
+        let menu = PeerMenu {
+            role: Role::Admin,
+            language: "ru".to_owned(),
+            peer: PackedChat {
+                ty: PackedType::User,
+                id: 42,
+                access_hash: Some(-7),
+            },
+        };
+        assert_eq!(menu.to_string().parse::<PeerMenu>().unwrap(), menu);
+        assert!("admin ru".parse::<PeerMenu>().is_err());
+        assert!("admin ru zz".parse::<PeerMenu>().is_err());
+        assert!("admin ru 0".parse::<PeerMenu>().is_err());
+    }
+
+    #[test]
+    fn help() {
+        let text = help_text("help.header", &commands(), |key| format!("<{key}>"));
+        assert_eq!(
+            text,
+            "<help.header>\n\n\
+             <modules.track>\n\
+             /track (<args.code>) - <commands.track>\n\
+             <usage.tracklist> - <commands.tracklist>\n\n\
+             <modules.general>\n\
+             /start - <commands.start>\n\n\
+             <modules.admin>\n\
+             /grant - <commands.grant>"
+        );
+    }
+
+    #[test]
+    fn menus() {
+        let commands = commands();
+        let text = |key: &str| key.to_uppercase();
+
+        let request = set_bot_commands(CommandScope::Group, "ru", &commands, text);
+        assert_eq!(request.lang_code, "ru");
+        assert_eq!(
+            names(&request),
+            ["track COMMANDS.TRACK", "tracklist COMMANDS.TRACKLIST"]
+        );
+
+        let request = set_bot_commands(CommandScope::Private, "", &commands, text);
+        assert_eq!(
+            names(&request),
+            [
+                "track COMMANDS.TRACK",
+                "start COMMANDS.START",
+                "tracklist COMMANDS.TRACKLIST"
+            ]
+        );
+
+        let peer = enums::InputPeer::Empty;
+        let request = set_peer_commands(peer, Role::Admin, &commands, text);
+        assert!(matches!(request.scope, enums::BotCommandScope::Peer(_)));
+        assert_eq!(names(&request).len(), 4);
+    }
+}
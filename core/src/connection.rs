@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use grammers_client::{InvocationError, ReconnectionPolicy};
+use std::{
+    ops::ControlFlow,
+    sync::{
+        Arc,
+        atomic::{AtomicU8, Ordering},
+    },
+    time::Duration,
+};
+
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+const BACKOFF_MAX: Duration = Duration::from_secs(300);
+
+/// ## ConnectionState
+/// State of the connection to Telegram as seen by the update loop
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connected,
+    Reconnecting,
+    Unauthorized,
+}
+
+/// ## Connection
+/// Shared handle to the current connection state
+#[derive(Clone)]
+pub struct Connection(Arc<AtomicU8>);
+
+impl Default for Connection {
+    fn default() -> Self {
+        Self(Arc::new(AtomicU8::new(ConnectionState::Connected as u8)))
+    }
+}
+
+impl Connection {
+    pub fn get(&self) -> ConnectionState {
+        match self.0.load(Ordering::SeqCst) {
+            0 => ConnectionState::Connected,
+            1 => ConnectionState::Reconnecting,
+            _ => ConnectionState::Unauthorized,
+        }
+    }
+
+    pub(crate) fn set(&self, state: ConnectionState) {
+        self.0.store(state as u8, Ordering::SeqCst);
+    }
+}
+
+pub(crate) enum ErrorKind {
+    /// Network or server issue that goes away by itself
+    Transient,
+    /// Session was invalidated and the bot has to sign in again
+    Unauthorized,
+}
+
+pub(crate) fn classify(error: &InvocationError) -> ErrorKind {
+    match error {
+        InvocationError::Rpc(rpc) if rpc.code == 401 => ErrorKind::Unauthorized,
+        _ => ErrorKind::Transient,
+    }
+}
+
+/// Delay before the next attempt, doubles with every attempt up to 5 minutes
+pub(crate) fn backoff(attempt: usize) -> Duration {
+    BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(attempt.min(16) as u32))
+        .min(BACKOFF_MAX)
+}
+
+/// ## Backoff
+/// Reconnection policy that keeps retrying forever with exponential backoff
+pub(crate) struct Backoff;
+
+impl ReconnectionPolicy for Backoff {
+    fn should_retry(&self, attempts: usize) -> ControlFlow<(), Duration> {
+        ControlFlow::Continue(backoff(attempts))
+    }
+}
@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{
+    client::{BotClient, IncomingCallbackQuery, IncomingMessage},
+    conversation_store::{ConversationStore, MemoryConversationStore},
+};
+use dashmap::DashMap;
+use grammers_client::Client;
+use std::{
+    fmt::Display,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::oneshot;
+
+/// ## ConversationKey
+/// Conversations are kept per user per chat
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ConversationKey {
+    pub chat_id: i64,
+    pub user_id: i64,
+}
+
+impl ConversationKey {
+    /// Key for the sender of the message in its chat, `None` for messages without a sender
+    pub fn from_message(message: &impl IncomingMessage) -> Option<Self> {
+        Some(Self {
+            chat_id: message.chat_id()?,
+            user_id: message.sender_id()?,
+        })
+    }
+
+    /// Key for the user who pressed the button in the chat of the message
+    pub fn from_callback_query(query: &impl IncomingCallbackQuery) -> Option<Self> {
+        Some(Self {
+            chat_id: query.chat_id()?,
+            user_id: query.sender_id()?,
+        })
+    }
+}
+
+/// ## Reply
+/// What the user answered with, a message or a button press
+#[derive(Clone)]
+pub enum Reply<C: BotClient = Client> {
+    Message(C::Message),
+    CallbackQuery(C::CallbackQuery),
+}
+
+impl<C: BotClient> Reply<C> {
+    pub fn key(&self) -> Option<ConversationKey> {
+        match self {
+            Reply::Message(message) => ConversationKey::from_message(message),
+            Reply::CallbackQuery(query) => ConversationKey::from_callback_query(query),
+        }
+    }
+}
+
+/// ## ConversationState
+/// Persistent state of a multi-step conversation, replies are passed to the handler registered under `name`
+#[derive(Clone, Debug)]
+pub struct ConversationState {
+    pub name: String,
+    /// Current step, meaning is up to the handler
+    pub step: String,
+    /// Data collected so far, meaning is up to the handler
+    pub data: String,
+    /// Unix time in seconds after which the conversation is dropped, set by `Conversations::set`
+    pub expires_at: u64,
+}
+
+impl ConversationState {
+    pub fn new(name: impl Into<String>, step: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            step: step.into(),
+            data: String::new(),
+            expires_at: 0,
+        }
+    }
+
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.data = data.into();
+        self
+    }
+}
+
+/// ## AskError
+/// Reason `Conversations::ask` returned without a reply
+#[derive(Debug, PartialEq, Eq)]
+pub enum AskError {
+    /// The user did not reply in time
+    Timeout,
+    /// The conversation was finished or another question was asked
+    Cancelled,
+}
+
+impl Display for AskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AskError::Timeout => write!(f, "no reply in time"),
+            AskError::Cancelled => write!(f, "conversation was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for AskError {}
+
+/// ## Conversations
+/// Conversation state of all users, replies that are not commands go to the question being awaited with `ask`,
+/// or to the handler of the persistent conversation if there is one
+#[derive(Clone)]
+pub struct Conversations<C: BotClient = Client> {
+    pub(crate) store: Arc<dyn ConversationStore>,
+    pub(crate) timeout: Duration,
+    waiters: Arc<DashMap<ConversationKey, oneshot::Sender<Reply<C>>>>,
+}
+
+impl<C: BotClient> Default for Conversations<C> {
+    fn default() -> Self {
+        Self {
+            store: Arc::new(MemoryConversationStore::new()),
+            timeout: Duration::from_secs(600),
+            waiters: Arc::new(DashMap::new()),
+        }
+    }
+}
+
+impl<C: BotClient> Conversations<C> {
+    /// Wait for the next reply of the user, asking again replaces the previous question which gets `AskError::Cancelled`.
+    /// Questions are not persisted, use `set` for conversations that have to survive restarts
+    pub async fn ask(&self, key: ConversationKey, timeout: Duration) -> Result<Reply<C>, AskError> {
+        let (sender, receiver) = oneshot::channel();
+        self.waiters.insert(key, sender);
+        let result = tokio::time::timeout(timeout, receiver).await;
+        match result {
+            Ok(Ok(reply)) => Ok(reply),
+            Ok(Err(_)) => Err(AskError::Cancelled),
+            Err(_) => {
+                self.waiters.remove_if(&key, |_, s| s.is_closed());
+                Err(AskError::Timeout)
+            }
+        }
+    }
+
+    /// Current conversation of the user, expired ones are removed
+    pub async fn get(&self, key: ConversationKey) -> anyhow::Result<Option<ConversationState>> {
+        match self.store.load(key).await? {
+            Some(state) if state.expires_at <= unix_now() => {
+                self.store.remove(key).await?;
+                Ok(None)
+            }
+            state => Ok(state),
+        }
+    }
+
+    /// Start or advance conversation of the user, the timeout starts over with every call
+    pub async fn set(
+        &self,
+        key: ConversationKey,
+        mut state: ConversationState,
+    ) -> anyhow::Result<()> {
+        state.expires_at = unix_now() + self.timeout.as_secs();
+        self.store.save(key, state).await
+    }
+
+    /// End conversation of the user and cancel the awaited question, returns `false` if there was nothing to end
+    pub async fn finish(&self, key: ConversationKey) -> anyhow::Result<bool> {
+        let waiting = self.waiters.remove(&key).is_some();
+        let active = self.get(key).await?.is_some();
+        if active {
+            self.store.remove(key).await?;
+        }
+        Ok(waiting || active)
+    }
+
+    /// Pass the reply to the awaited question, or return it with the conversation it belongs to
+    pub(crate) async fn route(
+        &self,
+        reply: Reply<C>,
+    ) -> anyhow::Result<Option<(ConversationState, Reply<C>)>> {
+        let Some(key) = reply.key() else {
+            return Ok(None);
+        };
+        let reply = match self.waiters.remove(&key) {
+            Some((_, sender)) => match sender.send(reply) {
+                Ok(()) => return Ok(None),
+                Err(reply) => reply,
+            },
+            None => reply,
+        };
+        Ok(self.get(key).await?.map(|state| (state, reply)))
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
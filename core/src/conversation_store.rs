@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::conversation::{ConversationKey, ConversationState};
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+
+/// ## ConversationStore
+/// Place where conversation state is kept, use a persistent one for conversations to survive restarts
+pub trait ConversationStore: Send + Sync {
+    fn load(
+        &self,
+        key: ConversationKey,
+    ) -> BoxFuture<'_, anyhow::Result<Option<ConversationState>>>;
+
+    fn save(
+        &self,
+        key: ConversationKey,
+        state: ConversationState,
+    ) -> BoxFuture<'_, anyhow::Result<()>>;
+
+    fn remove(&self, key: ConversationKey) -> BoxFuture<'_, anyhow::Result<()>>;
+}
+
+/// ## MemoryConversationStore
+/// Keeps conversations in memory, they are lost on restart
+#[derive(Default)]
+pub struct MemoryConversationStore {
+    states: DashMap<ConversationKey, ConversationState>,
+}
+
+impl MemoryConversationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ConversationStore for MemoryConversationStore {
+    fn load(
+        &self,
+        key: ConversationKey,
+    ) -> BoxFuture<'_, anyhow::Result<Option<ConversationState>>> {
+        let state = self.states.get(&key).map(|s| s.clone());
+        Box::pin(async move { Ok(state) })
+    }
+
+    fn save(
+        &self,
+        key: ConversationKey,
+        state: ConversationState,
+    ) -> BoxFuture<'_, anyhow::Result<()>> {
+        self.states.insert(key, state);
+        Box::pin(async move { Ok(()) })
+    }
+
+    fn remove(&self, key: ConversationKey) -> BoxFuture<'_, anyhow::Result<()>> {
+        self.states.remove(&key);
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+/// ## SqliteConversationStore
+/// Keeps conversations in the `conversations` table of a SQLite database
+#[cfg(feature = "sqlite")]
+pub struct SqliteConversationStore {
+    pool: sqlx::SqlitePool,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteConversationStore {
+    pub async fn new(pool: sqlx::SqlitePool) -> anyhow::Result<Self> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS conversations (chat_id INTEGER NOT NULL, user_id INTEGER NOT NULL, name TEXT NOT NULL, step TEXT NOT NULL, data TEXT NOT NULL, expires_at INTEGER NOT NULL, PRIMARY KEY (chat_id, user_id))",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl ConversationStore for SqliteConversationStore {
+    fn load(
+        &self,
+        key: ConversationKey,
+    ) -> BoxFuture<'_, anyhow::Result<Option<ConversationState>>> {
+        Box::pin(async move {
+            let row = sqlx::query_as::<_, (String, String, String, i64)>(
+                "SELECT name, step, data, expires_at FROM conversations WHERE chat_id = ? AND user_id = ?",
+            )
+            .bind(key.chat_id)
+            .bind(key.user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+            Ok(row.map(|(name, step, data, expires_at)| ConversationState {
+                name,
+                step,
+                data,
+                expires_at: expires_at as u64,
+            }))
+        })
+    }
+
+    fn save(
+        &self,
+        key: ConversationKey,
+        state: ConversationState,
+    ) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO conversations (chat_id, user_id, name, step, data, expires_at) VALUES (?, ?, ?, ?, ?, ?) ON CONFLICT (chat_id, user_id) DO UPDATE SET name = excluded.name, step = excluded.step, data = excluded.data, expires_at = excluded.expires_at",
+            )
+            .bind(key.chat_id)
+            .bind(key.user_id)
+            .bind(state.name)
+            .bind(state.step)
+            .bind(state.data)
+            .bind(state.expires_at as i64)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn remove(&self, key: ConversationKey) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(async move {
+            sqlx::query("DELETE FROM conversations WHERE chat_id = ? AND user_id = ?")
+                .bind(key.chat_id)
+                .bind(key.user_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{
+    Context,
+    callback_query_ext::CallbackAnswer,
+    client::{BotClient, IncomingInlineSend},
+    inline_message_ext::InlineEdit,
+    middleware::Event,
+};
+use grammers_client::types::PackedChat;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+/// ## ErrorReporter
+/// Decides what happens with errors returned by handlers, errors are always logged
+#[derive(Clone, Default)]
+pub struct ErrorReporter {
+    reply: Option<String>,
+    admin_chat: Option<PackedChat>,
+    admin_chat_failed: Arc<AtomicBool>,
+}
+
+impl ErrorReporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tell the user that something went wrong in their language, replies to messages, edits sent inline messages
+    /// and answers button presses with a toast
+    pub fn reply(mut self, key: impl Into<String>) -> Self {
+        self.reply = Some(key.into());
+        self
+    }
+
+    /// Send error details to the chat
+    pub const fn admin_chat(mut self, chat: PackedChat) -> Self {
+        self.admin_chat = Some(chat);
+        self
+    }
+
+    /// Report error returned by the handler of `event` that ran with `context`, scheduled jobs have no event
+    pub async fn report<State, C: BotClient>(
+        &self,
+        context: &Context<State, C>,
+        event: Option<&Event<C>>,
+        error: &anyhow::Error,
+    ) {
+        let client = &context.client;
+        let source = event.map(|e| e.kind()).unwrap_or("scheduled_job");
+        tracing::error!(source, error = ?error, "handler failed");
+
+        if let Some(key) = &self.reply {
+            let text = context.text(key, &[]);
+            match event {
+                Some(Event::Message(message) | Event::MessageEdited(message)) => {
+                    let _ = client.reply(message, text).await;
+                }
+                // Answering stops the loading indicator, it fails harmlessly if the handler already answered
+                Some(Event::CallbackQuery(query)) => {
+                    let _ = client
+                        .answer_callback_query(query, CallbackAnswer::toast(text))
+                        .await;
+                }
+                Some(Event::InlineSend(send)) => {
+                    if let Some(message_id) = send.message_id() {
+                        let _ = client
+                            .edit_inline_message(message_id, InlineEdit::text(text))
+                            .await;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(chat) = self.admin_chat {
+            let sender = event
+                .and_then(|e| e.sender_id())
+                .map(|id| format!(" from {id}"))
+                .unwrap_or_default();
+            let result = client
+                .send_message(chat, format!("{source} handler failed{sender}:\n{error:?}"))
+                .await;
+            match result {
+                Err(e) if !self.admin_chat_failed.swap(true, Ordering::Relaxed) => {
+                    tracing::warn!(error = %e, "failed to send error report to the admin chat, further failures are not logged");
+                }
+                _ => {}
+            }
+        }
+    }
+}
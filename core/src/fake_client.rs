@@ -0,0 +1,290 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{
+    callback_query_ext::CallbackAnswer,
+    client::{
+        BotClient, IncomingCallbackQuery, IncomingInlineQuery, IncomingInlineSend, IncomingMessage,
+        IncomingUpdate,
+    },
+    inline_message_ext::InlineEdit,
+};
+use grammers_client::{
+    InvocationError,
+    grammers_tl_types::{
+        enums::{self, InputBotInlineMessageId},
+        functions::{
+            bots::{ResetBotCommands, SetBotCommands},
+            messages::SetInlineBotResults,
+        },
+    },
+    session::PackedType,
+    types::PackedChat,
+};
+use std::sync::{Arc, Mutex};
+
+/// ## FakeClient
+/// Records the calls handlers make instead of sending them to Telegram
+#[derive(Clone, Default)]
+pub(crate) struct FakeClient {
+    calls: Arc<Mutex<Vec<Call>>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Call {
+    Reply {
+        chat_id: i64,
+        text: String,
+    },
+    SendMessage {
+        chat_id: i64,
+        text: String,
+    },
+    EditInlineMessage {
+        id: InputBotInlineMessageId,
+        text: Option<String>,
+    },
+    Answer {
+        query_id: i64,
+        titles: Vec<String>,
+    },
+    AnswerCallbackQuery {
+        query_id: i64,
+        message: Option<String>,
+        alert: bool,
+    },
+    SetBotCommands(SetBotCommands),
+    ResetBotCommands(ResetBotCommands),
+}
+
+impl FakeClient {
+    /// Calls recorded since the last take
+    pub(crate) fn take_calls(&self) -> Vec<Call> {
+        std::mem::take(&mut self.calls.lock().unwrap())
+    }
+
+    fn record(&self, call: Call) {
+        self.calls.lock().unwrap().push(call);
+    }
+}
+
+impl BotClient for FakeClient {
+    type Message = FakeMessage;
+    type CallbackQuery = FakeCallbackQuery;
+    type InlineQuery = FakeInlineQuery;
+    type InlineSend = FakeInlineSend;
+
+    async fn reply(&self, message: &FakeMessage, text: String) -> Result<(), InvocationError> {
+        self.record(Call::Reply {
+            chat_id: message.chat_id,
+            text,
+        });
+        Ok(())
+    }
+
+    async fn send_message(&self, chat: PackedChat, text: String) -> Result<(), InvocationError> {
+        self.record(Call::SendMessage {
+            chat_id: chat.id,
+            text,
+        });
+        Ok(())
+    }
+
+    async fn edit_inline_message(
+        &self,
+        id: InputBotInlineMessageId,
+        edit: InlineEdit,
+    ) -> Result<bool, InvocationError> {
+        self.record(Call::EditInlineMessage {
+            id,
+            text: edit.message,
+        });
+        Ok(true)
+    }
+
+    async fn answer(&self, request: SetInlineBotResults) -> Result<(), InvocationError> {
+        let titles = request
+            .results
+            .into_iter()
+            .filter_map(|result| match result {
+                enums::InputBotInlineResult::Result(result) => result.title,
+                _ => None,
+            })
+            .collect();
+        self.record(Call::Answer {
+            query_id: request.query_id,
+            titles,
+        });
+        Ok(())
+    }
+
+    async fn answer_callback_query(
+        &self,
+        query: &FakeCallbackQuery,
+        answer: CallbackAnswer,
+    ) -> Result<bool, InvocationError> {
+        self.record(Call::AnswerCallbackQuery {
+            query_id: query.query_id,
+            message: answer.message,
+            alert: answer.alert,
+        });
+        Ok(true)
+    }
+
+    async fn set_bot_commands(&self, request: SetBotCommands) -> Result<(), InvocationError> {
+        self.record(Call::SetBotCommands(request));
+        Ok(())
+    }
+
+    async fn reset_bot_commands(&self, request: ResetBotCommands) -> Result<(), InvocationError> {
+        self.record(Call::ResetBotCommands(request));
+        Ok(())
+    }
+}
+
+/// Message sent to the bot, private when the chat is the sender
+#[derive(Clone, Debug)]
+pub(crate) struct FakeMessage {
+    pub(crate) chat_id: i64,
+    pub(crate) sender_id: i64,
+    pub(crate) text: String,
+}
+
+impl FakeMessage {
+    pub(crate) fn private(sender_id: i64, text: impl Into<String>) -> Self {
+        Self {
+            chat_id: sender_id,
+            sender_id,
+            text: text.into(),
+        }
+    }
+
+    pub(crate) fn group(chat_id: i64, sender_id: i64, text: impl Into<String>) -> Self {
+        Self {
+            chat_id,
+            sender_id,
+            text: text.into(),
+        }
+    }
+}
+
+impl IncomingUpdate for FakeMessage {
+    fn sender_id(&self) -> Option<i64> {
+        Some(self.sender_id)
+    }
+
+    fn chat_id(&self) -> Option<i64> {
+        Some(self.chat_id)
+    }
+
+    fn lang_code(&self) -> Option<String> {
+        None
+    }
+}
+
+impl IncomingMessage for FakeMessage {
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn private_chat(&self) -> Option<PackedChat> {
+        (self.chat_id == self.sender_id).then_some(PackedChat {
+            ty: PackedType::User,
+            id: self.chat_id,
+            access_hash: None,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct FakeCallbackQuery {
+    pub(crate) query_id: i64,
+    pub(crate) sender_id: i64,
+    pub(crate) data: Vec<u8>,
+}
+
+impl IncomingUpdate for FakeCallbackQuery {
+    fn sender_id(&self) -> Option<i64> {
+        Some(self.sender_id)
+    }
+
+    fn chat_id(&self) -> Option<i64> {
+        Some(self.sender_id)
+    }
+
+    fn lang_code(&self) -> Option<String> {
+        None
+    }
+}
+
+impl IncomingCallbackQuery for FakeCallbackQuery {
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct FakeInlineQuery {
+    pub(crate) query_id: i64,
+    pub(crate) sender_id: i64,
+    pub(crate) text: String,
+}
+
+impl IncomingUpdate for FakeInlineQuery {
+    fn sender_id(&self) -> Option<i64> {
+        Some(self.sender_id)
+    }
+
+    fn chat_id(&self) -> Option<i64> {
+        None
+    }
+
+    fn lang_code(&self) -> Option<String> {
+        None
+    }
+}
+
+impl IncomingInlineQuery for FakeInlineQuery {
+    fn query_id(&self) -> i64 {
+        self.query_id
+    }
+
+    fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn offset(&self) -> &str {
+        ""
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct FakeInlineSend {
+    pub(crate) sender_id: i64,
+    pub(crate) result_id: String,
+    pub(crate) message_id: Option<InputBotInlineMessageId>,
+}
+
+impl IncomingUpdate for FakeInlineSend {
+    fn sender_id(&self) -> Option<i64> {
+        Some(self.sender_id)
+    }
+
+    fn chat_id(&self) -> Option<i64> {
+        None
+    }
+
+    fn lang_code(&self) -> Option<String> {
+        None
+    }
+}
+
+impl IncomingInlineSend for FakeInlineSend {
+    fn result_id(&self) -> &str {
+        &self.result_id
+    }
+
+    fn message_id(&self) -> Option<InputBotInlineMessageId> {
+        self.message_id.clone()
+    }
+}
@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{Context, client::BotClient};
+use futures::future::BoxFuture;
+use grammers_client::Client;
+use std::sync::Arc;
+
+/// ## Handler
+/// Anything that can handle an update, implemented for async functions and closures taking the context and `Args`,
+/// implement it by hand to use a struct as a handler
+pub trait Handler<State, Args, C: BotClient = Client>: Send + Sync + 'static {
+    fn call(
+        &self,
+        context: Context<State, C>,
+        args: Args,
+    ) -> BoxFuture<'static, anyhow::Result<()>>;
+}
+
+pub(crate) type BoxedHandler<State, Args, C = Client> = Arc<dyn Handler<State, Args, C>>;
+
+macro_rules! impl_handler {
+    ($($arg:ident),*) => {
+        impl<State, Bot: BotClient, Func, Fut, $($arg),*> Handler<State, ($($arg,)*), Bot> for Func
+        where
+            Func: Fn(Context<State, Bot>, $($arg),*) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+        {
+            #[allow(non_snake_case)]
+            fn call(
+                &self,
+                context: Context<State, Bot>,
+                ($($arg,)*): ($($arg,)*),
+            ) -> BoxFuture<'static, anyhow::Result<()>> {
+                Box::pin(self(context, $($arg),*))
+            }
+        }
+    };
+}
+
+impl_handler!();
+impl_handler!(A);
+impl_handler!(A, B);
+impl_handler!(A, B, C);
@@ -0,0 +1,348 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::user_store::{MemoryUserStore, UserStore};
+use dashmap::DashMap;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Arc, OnceLock},
+};
+
+/// Messages of built-in commands, used when no catalog has them
+const BUILTIN: &str = "
+usage = Usage: {usage}
+args.user_id = user id
+args.role = owner, admin, user or banned
+roles.denied = You are not allowed to do this
+roles.too_high = You can only manage roles below your own
+roles.fixed = Role of {user_id} is set in the config
+roles.granted = {user_id} is now {role}
+roles.revoked = Role of {user_id} was revoked
+roles.list = Roles:\\n{roles}
+roles.empty = Nobody has a role
+";
+
+fn builtin() -> &'static Catalog {
+    static CATALOG: OnceLock<Catalog> = OnceLock::new();
+    CATALOG.get_or_init(|| Catalog::parse(BUILTIN).expect("invalid built-in messages"))
+}
+
+/// ## Arg
+/// Value substituted for a `{name}` placeholder
+#[derive(Clone, Debug)]
+pub enum Arg {
+    Text(String),
+    /// Numbers can also pick the plural form when the placeholder is called `count`
+    Number(i64),
+}
+
+impl Display for Arg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Arg::Text(text) => f.write_str(text),
+            Arg::Number(number) => write!(f, "{number}"),
+        }
+    }
+}
+
+impl From<&str> for Arg {
+    fn from(text: &str) -> Self {
+        Arg::Text(text.to_owned())
+    }
+}
+
+impl From<String> for Arg {
+    fn from(text: String) -> Self {
+        Arg::Text(text)
+    }
+}
+
+impl From<&String> for Arg {
+    fn from(text: &String) -> Self {
+        Arg::Text(text.clone())
+    }
+}
+
+macro_rules! impl_number_arg {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Arg {
+                fn from(number: $t) -> Self {
+                    Arg::Number(i64::try_from(number).unwrap_or(i64::MAX))
+                }
+            }
+        )*
+    };
+}
+
+impl_number_arg!(i32, i64, u32, u64, usize);
+
+/// ## PluralCategory
+/// Form of a word used with a number
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PluralCategory {
+    One,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    /// Category of `count` in `language`, languages without their own rules use the English ones
+    pub fn of(language: &str, count: i64) -> Self {
+        let n = count.unsigned_abs();
+        match language {
+            "ru" | "uk" | "be" => match (n % 10, n % 100) {
+                (1, 11) => PluralCategory::Many,
+                (1, _) => PluralCategory::One,
+                (2..=4, 12..=14) => PluralCategory::Many,
+                (2..=4, _) => PluralCategory::Few,
+                _ => PluralCategory::Many,
+            },
+            _ if n == 1 => PluralCategory::One,
+            _ => PluralCategory::Other,
+        }
+    }
+
+    const fn suffix(self) -> &'static str {
+        match self {
+            PluralCategory::One => "one",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// ## Catalog
+/// Messages of one language
+#[derive(Clone, Default, Debug)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Parse `key = value` lines, empty lines and lines starting with `#` are skipped.
+    /// Values may contain `{name}` placeholders and `\n` for line breaks, plural forms are written as
+    /// `key.one`, `key.few`, `key.many` and `key.other` and are picked by the `count` argument
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut messages = HashMap::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                anyhow::bail!("line {}: expected `key = value`", number + 1);
+            };
+            let value = value.trim().replace("\\n", "\n");
+            messages.insert(key.trim().to_owned(), value);
+        }
+        Ok(Self { messages })
+    }
+
+    fn get(&self, language: &str, key: &str, args: &[(&str, Arg)]) -> Option<&str> {
+        if let Some(message) = self.messages.get(key) {
+            return Some(message);
+        }
+        let count = args.iter().find_map(|(name, arg)| match arg {
+            Arg::Number(number) if *name == "count" => Some(*number),
+            _ => None,
+        })?;
+        let suffix = PluralCategory::of(language, count).suffix();
+        self.messages
+            .get(&format!("{key}.{suffix}"))
+            .or_else(|| self.messages.get(&format!("{key}.other")))
+            .map(String::as_str)
+    }
+}
+
+/// Replace `{name}` placeholders with `args`, `{{` and `}}` are literal braces, unknown placeholders are kept as is
+fn format(message: &str, args: &[(&str, Arg)]) -> String {
+    let mut result = String::with_capacity(message.len());
+    let mut rest = message;
+    while let Some(start) = rest.find(['{', '}']) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            result.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+        if rest.starts_with('}') {
+            result.push('}');
+            rest = &rest[1..];
+            continue;
+        }
+        let arg = rest.find('}').and_then(|end| {
+            let (_, arg) = args.iter().find(|(name, _)| *name == &rest[1..end])?;
+            Some((end, arg))
+        });
+        match arg {
+            Some((end, arg)) => {
+                result.push_str(&arg.to_string());
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// ## Locales
+/// Catalogs of all languages and languages chosen by users.
+/// The language of a user is the one they chose, or the one of their Telegram app if there is a catalog for it, or the default one
+#[derive(Clone)]
+pub struct Locales {
+    catalogs: Arc<DashMap<String, Catalog>>,
+    pub(crate) default_language: String,
+    pub(crate) store: Arc<dyn UserStore<String>>,
+}
+
+impl Default for Locales {
+    fn default() -> Self {
+        Self {
+            catalogs: Arc::new(DashMap::new()),
+            default_language: "en".to_owned(),
+            store: Arc::new(MemoryUserStore::new()),
+        }
+    }
+}
+
+impl Locales {
+    pub(crate) fn add_catalog(&self, language: String, catalog: Catalog) {
+        self.catalogs.insert(language, catalog);
+    }
+
+    /// Languages that have a catalog, sorted
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<_> = self.catalogs.iter().map(|c| c.key().clone()).collect();
+        languages.sort();
+        languages
+    }
+
+    /// Message `key` in `language` with placeholders filled from `args`.
+    /// Falls back to the default language, then to built-in English messages, and to the key itself if none has it
+    pub fn text(&self, language: &str, key: &str, args: &[(&str, Arg)]) -> String {
+        for language in [language, self.default_language.as_str()] {
+            let message = self
+                .catalogs
+                .get(language)
+                .and_then(|catalog| Some(format(catalog.get(language, key, args)?, args)));
+            if let Some(message) = message {
+                return message;
+            }
+        }
+        builtin()
+            .get("en", key, args)
+            .map(|message| format(message, args))
+            .unwrap_or_else(|| key.to_owned())
+    }
+
+    /// Language to talk to the user in, `lang_code` is the language of their Telegram app if known
+    pub async fn language(&self, user_id: i64, lang_code: Option<&str>) -> String {
+        match self.store.load(user_id).await {
+            Ok(Some(language)) if self.catalogs.contains_key(&language) => return language,
+            Ok(_) => {}
+            Err(e) => tracing::warn!(user_id, error = ?e, "failed to load language"),
+        }
+        lang_code
+            .map(|code| code.split(['-', '_']).next().unwrap_or(code).to_lowercase())
+            .filter(|language| self.catalogs.contains_key(language))
+            .unwrap_or_else(|| self.default_language.clone())
+    }
+
+    /// Make the user always get `language`, or follow their Telegram app again if it is `None`.
+    /// Returns `false` if there is no catalog for `language`
+    pub async fn set_language(&self, user_id: i64, language: Option<&str>) -> anyhow::Result<bool> {
+        match language {
+            Some(language) if !self.catalogs.contains_key(language) => Ok(false),
+            Some(language) => {
+                self.store.save(user_id, language.to_owned()).await?;
+                Ok(true)
+            }
+            None => {
+                self.store.remove(user_id).await?;
+                Ok(true)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plurals() {
+        let ru = |count| PluralCategory::of("ru", count);
+        assert_eq!(ru(1), PluralCategory::One);
+        assert_eq!(ru(2), PluralCategory::Few);
+        assert_eq!(ru(5), PluralCategory::Many);
+        assert_eq!(ru(11), PluralCategory::Many);
+        assert_eq!(ru(12), PluralCategory::Many);
+        assert_eq!(ru(21), PluralCategory::One);
+        assert_eq!(ru(22), PluralCategory::Few);
+        assert_eq!(ru(0), PluralCategory::Many);
+        assert_eq!(ru(-1), PluralCategory::One);
+        assert_eq!(PluralCategory::of("en", 1), PluralCategory::One);
+        assert_eq!(PluralCategory::of("en", 21), PluralCategory::Other);
+        assert_eq!(PluralCategory::of("de", 0), PluralCategory::Other);
+    }
+
+    #[test]
+    fn placeholders() {
+        let args = [("name", Arg::from("{count}")), ("count", Arg::from(3))];
+        assert_eq!(format("{name}: {count}", &args), "{count}: 3");
+        assert_eq!(format("{{name}} {{{count}}}", &args), "{name} {3}");
+        assert_eq!(format("{unknown} {", &args), "{unknown} {");
+        assert_eq!(format("} }}", &args), "} }");
+    }
+
+    #[test]
+    fn texts() {
+        let locales = Locales::default();
+        locales.add_catalog(
+            "ru".to_owned(),
+            Catalog::parse(
+                "# tracks\n\
+                 tracks.one = {count} трек\n\
+                 tracks.few = {count} трека\n\
+                 tracks.many = {count} треков\n\
+                 roles.empty = Ни у кого нет ролей",
+            )
+            .unwrap(),
+        );
+        locales.add_catalog(
+            "en".to_owned(),
+            Catalog::parse(
+                "tracks.one = {count} track\ntracks.other = {count} tracks\nhello = Hello\\nworld",
+            )
+            .unwrap(),
+        );
+
+        let tracks =
+            |language, count: i64| locales.text(language, "tracks", &[("count", count.into())]);
+        assert_eq!(tracks("ru", 1), "1 трек");
+        assert_eq!(tracks("ru", 2), "2 трека");
+        assert_eq!(tracks("ru", 5), "5 треков");
+        assert_eq!(tracks("ru", 11), "11 треков");
+        assert_eq!(tracks("ru", 12), "12 треков");
+        assert_eq!(tracks("ru", 21), "21 трек");
+        assert_eq!(tracks("en", 21), "21 tracks");
+
+        assert_eq!(locales.text("ru", "hello", &[]), "Hello\nworld");
+        assert_eq!(
+            locales.text("ru", "roles.empty", &[]),
+            "Ни у кого нет ролей"
+        );
+        assert_eq!(locales.text("en", "roles.empty", &[]), "Nobody has a role");
+        assert_eq!(locales.text("en", "missing", &[]), "missing");
+        assert!(Catalog::parse("no value").is_err());
+    }
+}
@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::inline_result::{generate_random_id, thumbnail};
+use grammers_client::{grammers_tl_types, reply_markup::ReplyMarkup};
+
+/// ## InlineArticle
+/// Text result, sends `text` or the title if there is no text
+pub struct InlineArticle {
+    id: Option<String>,
+    title: String,
+    description: Option<String>,
+    text: Option<String>,
+    url: Option<String>,
+    thumbnail: Option<String>,
+    thumbnail_mime: Option<String>,
+    reply_markup: Option<grammers_tl_types::enums::ReplyMarkup>,
+}
+
+impl InlineArticle {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            title: title.into(),
+            description: None,
+            text: None,
+            url: None,
+            thumbnail: None,
+            thumbnail_mime: None,
+            reply_markup: None,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Line shown under the title in the results
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    pub fn thumbnail(mut self, thumbnail: impl Into<String>) -> Self {
+        self.thumbnail = Some(thumbnail.into());
+        self
+    }
+
+    /// MIME type of the thumbnail, `image/jpeg` by default
+    pub fn thumbnail_mime(mut self, mime: impl Into<String>) -> Self {
+        self.thumbnail_mime = Some(mime.into());
+        self
+    }
+
+    pub fn reply_markup<RM: ReplyMarkup>(mut self, markup: &RM) -> Self {
+        self.reply_markup = Some(markup.to_reply_markup().raw);
+        self
+    }
+}
+
+impl From<InlineArticle> for grammers_tl_types::enums::InputBotInlineResult {
+    fn from(value: InlineArticle) -> Self {
+        Self::Result(grammers_tl_types::types::InputBotInlineResult {
+            id: value.id.unwrap_or_else(generate_random_id),
+            r#type: "article".to_string(),
+            title: Some(value.title.clone()),
+            description: value.description,
+            url: value.url,
+            thumb: thumbnail(value.thumbnail, value.thumbnail_mime),
+            content: None,
+            send_message: grammers_tl_types::enums::InputBotInlineMessage::Text(
+                grammers_tl_types::types::InputBotInlineMessageText {
+                    no_webpage: false,
+                    invert_media: false,
+                    message: value.text.unwrap_or(value.title),
+                    entities: None,
+                    reply_markup: value.reply_markup,
+                },
+            ),
+        })
+    }
+}
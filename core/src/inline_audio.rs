@@ -1,17 +1,15 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
+use crate::inline_result::{generate_random_id, media_message, thumbnail, web_document};
 use grammers_client::{grammers_tl_types, reply_markup::ReplyMarkup};
-use std::{
-    sync::atomic::{AtomicI64, Ordering},
-    time::SystemTime,
-};
 
 pub struct InlineAudio {
     id: Option<String>,
     url: String,
     text: Option<String>,
     thumbnail: Option<String>,
+    thumbnail_mime: Option<String>,
     mime: Option<String>,
     title: Option<String>,
     performer: Option<String>,
@@ -19,23 +17,6 @@ pub struct InlineAudio {
     reply_markup: Option<grammers_tl_types::enums::ReplyMarkup>,
 }
 
-static LAST_ID: AtomicI64 = AtomicI64::new(0);
-
-fn generate_random_id() -> i64 {
-    if LAST_ID.load(Ordering::SeqCst) == 0 {
-        let now = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .expect("system time is before epoch")
-            .as_nanos() as i64;
-
-        LAST_ID
-            .compare_exchange(0, now, Ordering::SeqCst, Ordering::SeqCst)
-            .unwrap();
-    }
-
-    LAST_ID.fetch_add(1, Ordering::SeqCst)
-}
-
 impl InlineAudio {
     pub const fn new(url: String) -> Self {
         Self {
@@ -43,6 +24,7 @@ impl InlineAudio {
             url,
             text: None,
             thumbnail: None,
+            thumbnail_mime: None,
             mime: None,
             title: None,
             performer: None,
@@ -71,6 +53,12 @@ impl InlineAudio {
         self
     }
 
+    /// MIME type of the thumbnail, `image/jpeg` by default
+    pub fn thumbnail_mime(mut self, mime: impl Into<String>) -> Self {
+        self.thumbnail_mime = Some(mime.into());
+        self
+    }
+
     pub fn mime(mut self, mime: impl Into<String>) -> Self {
         self.mime = Some(mime.into());
         self
@@ -100,45 +88,26 @@ impl InlineAudio {
 impl From<InlineAudio> for grammers_tl_types::enums::InputBotInlineResult {
     fn from(value: InlineAudio) -> Self {
         Self::Result(grammers_tl_types::types::InputBotInlineResult {
-            id: value.id.unwrap_or_else(|| generate_random_id().to_string()),
+            id: value.id.unwrap_or_else(generate_random_id),
             r#type: "audio".to_string(),
             title: value.title.clone(),
             description: value.performer.clone(),
             url: None,
-            thumb: value.thumbnail.map(|u| {
-                grammers_tl_types::enums::InputWebDocument::Document(
-                    grammers_tl_types::types::InputWebDocument {
-                        url: u,
-                        size: 0,
-                        mime_type: "image/jpeg".to_string(),
-                        attributes: vec![],
+            thumb: thumbnail(value.thumbnail, value.thumbnail_mime),
+            content: Some(web_document(
+                value.url,
+                value.mime.unwrap_or_else(|| "audio/mpeg".to_string()),
+                vec![grammers_tl_types::enums::DocumentAttribute::Audio(
+                    grammers_tl_types::types::DocumentAttributeAudio {
+                        voice: false,
+                        duration: value.duration.unwrap_or(0),
+                        title: value.title,
+                        performer: value.performer,
+                        waveform: None,
                     },
-                )
-            }),
-            content: Some(grammers_tl_types::enums::InputWebDocument::Document(
-                grammers_tl_types::types::InputWebDocument {
-                    url: value.url,
-                    size: 0,
-                    mime_type: value.mime.unwrap_or_else(|| "audio/mpeg".to_string()),
-                    attributes: vec![grammers_tl_types::enums::DocumentAttribute::Audio(
-                        grammers_tl_types::types::DocumentAttributeAudio {
-                            voice: false,
-                            duration: value.duration.unwrap_or(0),
-                            title: value.title,
-                            performer: value.performer,
-                            waveform: None,
-                        },
-                    )],
-                },
+                )],
             )),
-            send_message: grammers_tl_types::enums::InputBotInlineMessage::MediaAuto(
-                grammers_tl_types::types::InputBotInlineMessageMediaAuto {
-                    invert_media: false,
-                    message: value.text.unwrap_or_default(),
-                    entities: None,
-                    reply_markup: value.reply_markup,
-                },
-            ),
+            send_message: media_message(value.text, value.reply_markup),
         })
     }
 }
@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::inline_result::{generate_random_id, media_message};
+use grammers_client::{grammers_tl_types, reply_markup::ReplyMarkup};
+
+enum CachedMedia {
+    Photo(grammers_tl_types::enums::InputPhoto),
+    Document(String, grammers_tl_types::enums::InputDocument),
+}
+
+/// ## InlineCached
+/// Result that sends a file which is already uploaded to Telegram, nothing is downloaded from URLs
+pub struct InlineCached {
+    id: Option<String>,
+    media: CachedMedia,
+    title: Option<String>,
+    description: Option<String>,
+    text: Option<String>,
+    reply_markup: Option<grammers_tl_types::enums::ReplyMarkup>,
+}
+
+impl InlineCached {
+    fn new(media: CachedMedia) -> Self {
+        Self {
+            id: None,
+            media,
+            title: None,
+            description: None,
+            text: None,
+            reply_markup: None,
+        }
+    }
+
+    pub fn photo(photo: grammers_tl_types::enums::InputPhoto) -> Self {
+        Self::new(CachedMedia::Photo(photo))
+    }
+
+    /// Document of `kind`, one of `audio`, `voice`, `video`, `document`, `gif`, `mpeg4_gif` or `sticker`
+    pub fn document(
+        kind: impl Into<String>,
+        document: grammers_tl_types::enums::InputDocument,
+    ) -> Self {
+        Self::new(CachedMedia::Document(kind.into(), document))
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Title of the result, not used for photos
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Description of the result, not used for photos
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn reply_markup<RM: ReplyMarkup>(mut self, markup: &RM) -> Self {
+        self.reply_markup = Some(markup.to_reply_markup().raw);
+        self
+    }
+}
+
+impl From<InlineCached> for grammers_tl_types::enums::InputBotInlineResult {
+    fn from(value: InlineCached) -> Self {
+        let id = value.id.unwrap_or_else(generate_random_id);
+        let send_message = media_message(value.text, value.reply_markup);
+        match value.media {
+            CachedMedia::Photo(photo) => {
+                Self::Photo(grammers_tl_types::types::InputBotInlineResultPhoto {
+                    id,
+                    r#type: "photo".to_string(),
+                    photo,
+                    send_message,
+                })
+            }
+            CachedMedia::Document(kind, document) => {
+                Self::Document(grammers_tl_types::types::InputBotInlineResultDocument {
+                    id,
+                    r#type: kind,
+                    title: value.title,
+                    description: value.description,
+                    document,
+                    send_message,
+                })
+            }
+        }
+    }
+}
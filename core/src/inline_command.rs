@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{client::BotClient, handler::BoxedHandler, role::Role};
+use dashmap::mapref::one::RefMut;
+use grammers_client::Client;
+
+pub(crate) struct InlineCommandData<State, C: BotClient> {
+    pub(crate) query: BoxedHandler<State, (C::InlineQuery, Vec<String>), C>,
+    pub(crate) send: BoxedHandler<State, (C::InlineSend, Vec<String>), C>,
+    pub(crate) role: Role,
+}
+
+/// ## InlineCommandOptions
+/// Returned by `add_inline_command`, sets who can use the inline command
+pub struct InlineCommandOptions<'a, State, C: BotClient = Client> {
+    pub(crate) data: RefMut<'a, String, InlineCommandData<State, C>>,
+}
+
+impl<State, C: BotClient> InlineCommandOptions<'_, State, C> {
+    /// Minimum role needed to use the inline command, `Role::User` by default
+    pub fn role(mut self, role: Role) -> Self {
+        self.data.role = role;
+        self
+    }
+}
+
+/// Separator between the command name and the rest of inline result id
+pub const SEPARATOR: char = '|';
+
+/// Build inline result id that will be routed back to the send handler of `command`
+pub fn result_id(command: &str, parts: impl IntoIterator<Item = impl AsRef<str>>) -> String {
+    let mut id = command.to_owned();
+    for part in parts {
+        id.push(SEPARATOR);
+        id.push_str(part.as_ref());
+    }
+    id
+}
+
+/// Split inline query text into command name and arguments
+pub fn parse_query(text: &str) -> Option<(&str, Vec<String>)> {
+    let mut words = text.split_whitespace();
+    let command = words.next()?;
+    Some((command, words.map(|s| s.to_owned()).collect()))
+}
+
+/// Split inline result id into command name and the parts passed to `result_id`
+pub fn parse_result_id(id: &str) -> (&str, Vec<String>) {
+    let mut parts = id.split(SEPARATOR);
+    let command = parts.next().unwrap_or_default();
+    (command, parts.map(|s| s.to_owned()).collect())
+}
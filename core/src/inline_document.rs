@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::inline_result::{generate_random_id, media_message, thumbnail, web_document};
+use grammers_client::{grammers_tl_types, reply_markup::ReplyMarkup};
+
+/// ## InlineDocument
+/// File result, Telegram only accepts `application/pdf` and `application/zip` files from URLs
+pub struct InlineDocument {
+    id: Option<String>,
+    url: String,
+    mime: String,
+    title: String,
+    description: Option<String>,
+    text: Option<String>,
+    thumbnail: Option<String>,
+    thumbnail_mime: Option<String>,
+    reply_markup: Option<grammers_tl_types::enums::ReplyMarkup>,
+}
+
+impl InlineDocument {
+    pub fn new(url: impl Into<String>, mime: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            url: url.into(),
+            mime: mime.into(),
+            title: title.into(),
+            description: None,
+            text: None,
+            thumbnail: None,
+            thumbnail_mime: None,
+            reply_markup: None,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn thumbnail(mut self, thumbnail: impl Into<String>) -> Self {
+        self.thumbnail = Some(thumbnail.into());
+        self
+    }
+
+    /// MIME type of the thumbnail, `image/jpeg` by default
+    pub fn thumbnail_mime(mut self, mime: impl Into<String>) -> Self {
+        self.thumbnail_mime = Some(mime.into());
+        self
+    }
+
+    pub fn reply_markup<RM: ReplyMarkup>(mut self, markup: &RM) -> Self {
+        self.reply_markup = Some(markup.to_reply_markup().raw);
+        self
+    }
+}
+
+impl From<InlineDocument> for grammers_tl_types::enums::InputBotInlineResult {
+    fn from(value: InlineDocument) -> Self {
+        Self::Result(grammers_tl_types::types::InputBotInlineResult {
+            id: value.id.unwrap_or_else(generate_random_id),
+            r#type: "document".to_string(),
+            title: Some(value.title),
+            description: value.description,
+            url: None,
+            thumb: thumbnail(value.thumbnail, value.thumbnail_mime),
+            content: Some(web_document(value.url, value.mime, vec![])),
+            send_message: media_message(value.text, value.reply_markup),
+        })
+    }
+}
@@ -2,44 +2,122 @@
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
 use grammers_client::{
-    Client, InvocationError, button,
+    Client, InvocationError,
     grammers_tl_types::{
         self,
-        enums::{InputBotInlineMessageId, InputMedia},
+        enums::{InputBotInlineMessageId, InputMedia, MessageEntity},
     },
-    reply_markup::{self, ReplyMarkup},
+    parsers,
+    reply_markup::ReplyMarkup,
 };
 
+/// ## InlineEdit
+/// New content of an inline message, whatever is not set is kept as is except for the keyboard which is removed
+#[derive(Clone, Default)]
+pub struct InlineEdit {
+    pub(crate) message: Option<String>,
+    entities: Option<Vec<MessageEntity>>,
+    media: Option<InputMedia>,
+    reply_markup: Option<grammers_tl_types::enums::ReplyMarkup>,
+    link_preview: bool,
+}
+
+impl InlineEdit {
+    /// Keep text and media, useful to only change the keyboard
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Plain text, or the caption if the message has media
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            message: Some(text.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Text with markdown formatting
+    pub fn markdown(text: impl AsRef<str>) -> Self {
+        let (message, entities) = parsers::parse_markdown_message(text.as_ref());
+        Self {
+            message: Some(message),
+            entities: Some(entities),
+            ..Default::default()
+        }
+    }
+
+    /// Text with HTML formatting
+    pub fn html(text: impl AsRef<str>) -> Self {
+        let (message, entities) = parsers::parse_html_message(text.as_ref());
+        Self {
+            message: Some(message),
+            entities: Some(entities),
+            ..Default::default()
+        }
+    }
+
+    /// Replace media of the message, existing media is kept if this is not set
+    pub fn media(mut self, media: InputMedia) -> Self {
+        self.media = Some(media);
+        self
+    }
+
+    /// Inline keyboard with any buttons `grammers_client::button` can build
+    pub fn reply_markup<RM: ReplyMarkup>(mut self, markup: &RM) -> Self {
+        self.reply_markup = Some(markup.to_reply_markup().raw);
+        self
+    }
+
+    pub(crate) fn raw_reply_markup(
+        mut self,
+        reply_markup: Option<grammers_tl_types::enums::ReplyMarkup>,
+    ) -> Self {
+        self.reply_markup = reply_markup;
+        self
+    }
+
+    /// Show preview of the first link in the text, disabled by default
+    pub const fn link_preview(mut self, link_preview: bool) -> Self {
+        self.link_preview = link_preview;
+        self
+    }
+}
+
+impl From<&str> for InlineEdit {
+    fn from(text: &str) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<String> for InlineEdit {
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
 pub trait InlineMessageExt {
-    fn edit_inline_message_ext<S: AsRef<str> + Send>(
+    fn edit_inline_message_ext(
         &self,
         id: InputBotInlineMessageId,
-        text: S,
-        button_text: Option<S>,
-        media: Option<InputMedia>,
+        edit: impl Into<InlineEdit> + Send,
     ) -> impl Future<Output = Result<bool, InvocationError>> + Send;
 }
 
 impl InlineMessageExt for Client {
-    async fn edit_inline_message_ext<S: AsRef<str> + Send>(
+    async fn edit_inline_message_ext(
         &self,
         id: InputBotInlineMessageId,
-        text: S,
-        button_text: Option<S>,
-        media: Option<InputMedia>,
+        edit: impl Into<InlineEdit> + Send,
     ) -> Result<bool, InvocationError> {
+        let edit = edit.into();
         self.invoke(
             &grammers_tl_types::functions::messages::EditInlineBotMessage {
                 id,
-                message: Some(text.as_ref().to_owned()),
-                media,
-                entities: None,
-                no_webpage: true,
-                reply_markup: button_text.map(|t| {
-                    reply_markup::inline(vec![vec![button::inline(t.as_ref(), b"0")]])
-                        .to_reply_markup()
-                        .raw
-                }),
+                message: edit.message,
+                media: edit.media,
+                entities: edit.entities,
+                no_webpage: !edit.link_preview,
+                reply_markup: edit.reply_markup,
                 invert_media: false,
             },
         )
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::inline_result::{generate_random_id, media_message, thumbnail, web_document};
+use grammers_client::{grammers_tl_types, reply_markup::ReplyMarkup};
+
+/// ## InlinePhoto
+/// Photo result, the photo itself is used as the thumbnail unless another one is set
+pub struct InlinePhoto {
+    id: Option<String>,
+    url: String,
+    mime: Option<String>,
+    size: Option<(i32, i32)>,
+    title: Option<String>,
+    description: Option<String>,
+    text: Option<String>,
+    thumbnail: Option<String>,
+    thumbnail_mime: Option<String>,
+    reply_markup: Option<grammers_tl_types::enums::ReplyMarkup>,
+}
+
+impl InlinePhoto {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            url: url.into(),
+            mime: None,
+            size: None,
+            title: None,
+            description: None,
+            text: None,
+            thumbnail: None,
+            thumbnail_mime: None,
+            reply_markup: None,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// MIME type of the photo, `image/jpeg` by default
+    pub fn mime(mut self, mime: impl Into<String>) -> Self {
+        self.mime = Some(mime.into());
+        self
+    }
+
+    pub const fn size(mut self, width: i32, height: i32) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    pub fn thumbnail(mut self, thumbnail: impl Into<String>) -> Self {
+        self.thumbnail = Some(thumbnail.into());
+        self
+    }
+
+    /// MIME type of the thumbnail, `image/jpeg` by default
+    pub fn thumbnail_mime(mut self, mime: impl Into<String>) -> Self {
+        self.thumbnail_mime = Some(mime.into());
+        self
+    }
+
+    pub fn reply_markup<RM: ReplyMarkup>(mut self, markup: &RM) -> Self {
+        self.reply_markup = Some(markup.to_reply_markup().raw);
+        self
+    }
+}
+
+impl From<InlinePhoto> for grammers_tl_types::enums::InputBotInlineResult {
+    fn from(value: InlinePhoto) -> Self {
+        let mime = value.mime.unwrap_or_else(|| "image/jpeg".to_string());
+        let (thumb, thumb_mime) = match value.thumbnail {
+            Some(thumbnail) => (thumbnail, value.thumbnail_mime),
+            None => (value.url.clone(), Some(mime.clone())),
+        };
+        Self::Result(grammers_tl_types::types::InputBotInlineResult {
+            id: value.id.unwrap_or_else(generate_random_id),
+            r#type: "photo".to_string(),
+            title: value.title,
+            description: value.description,
+            url: None,
+            thumb: thumbnail(Some(thumb), thumb_mime),
+            content: Some(web_document(
+                value.url,
+                mime,
+                value
+                    .size
+                    .map(|(w, h)| {
+                        grammers_tl_types::enums::DocumentAttribute::ImageSize(
+                            grammers_tl_types::types::DocumentAttributeImageSize { w, h },
+                        )
+                    })
+                    .into_iter()
+                    .collect(),
+            )),
+            send_message: media_message(value.text, value.reply_markup),
+        })
+    }
+}
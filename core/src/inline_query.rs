@@ -1,27 +1,49 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
-use grammers_client::{InvocationError, grammers_tl_types};
+use crate::client::{BotClient, IncomingInlineQuery};
+use dashmap::DashMap;
+use grammers_client::{Client, InvocationError, grammers_tl_types};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 /// ## InlineQuery
 /// Custom inline query
-pub struct InlineQuery {
-    query: grammers_client::types::InlineQuery,
-    client: grammers_client::Client,
+pub struct InlineQuery<C: BotClient = Client> {
+    query: C::InlineQuery,
+    client: C,
 }
 
-impl InlineQuery {
-    pub const fn new(
-        query: grammers_client::types::InlineQuery,
-        client: grammers_client::Client,
-    ) -> Self {
+impl<C: BotClient> InlineQuery<C> {
+    pub const fn new(query: C::InlineQuery, client: C) -> Self {
         Self { query, client }
     }
 
+    /// Offset the user scrolled to, empty for the first page
+    pub fn offset(&self) -> &str {
+        self.query.offset()
+    }
+
+    /// Take the page of `items` the user scrolled to, returns the page and the offset of the next one for `Answer::next_offset`
+    pub fn page<T>(
+        &self,
+        items: impl IntoIterator<Item = T>,
+        page_size: usize,
+    ) -> (Vec<T>, Option<String>) {
+        page(self.offset(), items, page_size)
+    }
+
+    /// Number of items `page` needs for the page the user scrolled to, pass it to `SearchCache::get_or_search`
+    pub fn needed(&self, page_size: usize) -> usize {
+        needed(self.offset(), page_size)
+    }
+
     pub fn answer(
         self,
         results: impl IntoIterator<Item = impl Into<grammers_tl_types::enums::InputBotInlineResult>>,
-    ) -> Answer {
+    ) -> Answer<C> {
         Answer {
             request: grammers_tl_types::functions::messages::SetInlineBotResults {
                 gallery: false,
@@ -38,14 +60,203 @@ impl InlineQuery {
     }
 }
 
-pub struct Answer {
+/// Take the page of `items` starting at `offset`, an empty or invalid offset is the first page
+pub fn page<T>(
+    offset: &str,
+    items: impl IntoIterator<Item = T>,
+    page_size: usize,
+) -> (Vec<T>, Option<String>) {
+    let start = offset.parse().unwrap_or(0);
+    let mut items = items.into_iter().skip(start);
+    let page: Vec<_> = items.by_ref().take(page_size).collect();
+    let next_offset = items
+        .next()
+        .is_some()
+        .then(|| (start + page.len()).to_string());
+    (page, next_offset)
+}
+
+/// Number of items `page` needs to take the page at `offset` and tell if there is a next one
+pub fn needed(offset: &str, page_size: usize) -> usize {
+    offset.parse().unwrap_or(0) + page_size + 1
+}
+
+/// ## SearchCache
+/// Search results kept for `ttl`, so scrolling to the next page of an inline query only searches the service pages not seen yet
+pub struct SearchCache<T> {
+    ttl: Duration,
+    entries: DashMap<String, Searched<T>>,
+}
+
+struct Searched<T> {
+    searched_at: Instant,
+    items: Arc<Vec<T>>,
+    /// Service page to search next, `None` after a page came back empty
+    next_page: Option<u32>,
+}
+
+impl<T: Clone> SearchCache<T> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: DashMap::new(),
+        }
+    }
+
+    /// Results cached for `key` with at least `len` items if the service has them, `search` is called with the next service page until there are enough or a page comes back empty
+    pub async fn get_or_search<E, F>(
+        &self,
+        key: &str,
+        len: usize,
+        mut search: impl FnMut(u32) -> F,
+    ) -> Result<Arc<Vec<T>>, E>
+    where
+        F: Future<Output = Result<Vec<T>, E>>,
+    {
+        let (mut items, mut next_page) = self
+            .entries
+            .get(key)
+            .filter(|entry| entry.searched_at.elapsed() < self.ttl)
+            .map(|entry| (entry.items.clone(), entry.next_page))
+            .unwrap_or((Arc::default(), Some(0)));
+        while let Some(page) = next_page.filter(|_| items.len() < len) {
+            let found = search(page).await?;
+            self.entries
+                .retain(|_, entry| entry.searched_at.elapsed() < self.ttl);
+            let mut entry = self
+                .entries
+                .entry(key.to_owned())
+                .or_insert_with(|| Searched {
+                    searched_at: Instant::now(),
+                    items: Arc::default(),
+                    next_page: Some(0),
+                });
+            // Another query for the same key may have added this page or the entry expired while searching
+            if entry.next_page == Some(page) {
+                entry.next_page = (!found.is_empty()).then_some(page + 1);
+                entry.items = Arc::new(entry.items.iter().cloned().chain(found).collect());
+            }
+            (items, next_page) = (entry.items.clone(), entry.next_page);
+        }
+        Ok(items)
+    }
+}
+
+/// ## Answer
+/// Inline query answer that is sent with `send`
+pub struct Answer<C: BotClient = Client> {
     request: grammers_tl_types::functions::messages::SetInlineBotResults,
-    client: grammers_client::Client,
+    client: C,
 }
 
-impl Answer {
+impl<C: BotClient> Answer<C> {
+    /// How long Telegram caches the results in seconds, 0 by default
+    pub const fn cache_time(mut self, seconds: i32) -> Self {
+        self.request.cache_time = seconds;
+        self
+    }
+
+    /// Cache the results only for the user who sent the query, `true` by default, disable it for results that are the same for everyone
+    pub const fn private(mut self, private: bool) -> Self {
+        self.request.private = private;
+        self
+    }
+
+    /// Show the results as a grid of media instead of a list
+    pub const fn gallery(mut self, gallery: bool) -> Self {
+        self.request.gallery = gallery;
+        self
+    }
+
+    /// Show a button above the results that opens a private chat with the bot and sends `/start start_param`
+    pub fn switch_pm(mut self, text: impl Into<String>, start_param: impl Into<String>) -> Self {
+        self.request.switch_pm = Some(grammers_tl_types::enums::InlineBotSwitchPm::Pm(
+            grammers_tl_types::types::InlineBotSwitchPm {
+                text: text.into(),
+                start_param: start_param.into(),
+            },
+        ));
+        self
+    }
+
+    /// Offset Telegram sends back when the user scrolls to the end of the results, `None` if there are no more results
+    pub fn next_offset(mut self, offset: Option<String>) -> Self {
+        self.request.next_offset = offset;
+        self
+    }
+
     pub async fn send(self) -> Result<(), InvocationError> {
-        self.client.invoke(&self.request).await?;
-        Ok(())
+        self.client.answer(self.request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages() {
+        assert_eq!(
+            page("", 0..25, 10),
+            ((0..10).collect(), Some("10".to_owned()))
+        );
+        assert_eq!(
+            page("10", 0..25, 10),
+            ((10..20).collect(), Some("20".to_owned()))
+        );
+        assert_eq!(page("20", 0..25, 10), ((20..25).collect(), None));
+        assert_eq!(page("10", 0..20, 10), ((10..20).collect(), None));
+        assert_eq!(page("30", 0..25, 10), (vec![], None));
+        assert_eq!(page("garbage", 0..5, 10), ((0..5).collect(), None));
+        assert_eq!(needed("", 10), 11);
+        assert_eq!(needed("20", 10), 31);
+    }
+
+    #[tokio::test]
+    async fn search_cache() {
+        let cache = SearchCache::new(Duration::from_secs(60));
+        let mut searched = vec![];
+        let mut search = |page| {
+            searched.push(page);
+            async move {
+                Ok::<_, ()>(match page {
+                    0 => vec![1, 2],
+                    1 => vec![3, 4],
+                    _ => vec![],
+                })
+            }
+        };
+        assert_eq!(
+            *cache.get_or_search("a", 1, &mut search).await.unwrap(),
+            [1, 2]
+        );
+        assert_eq!(
+            *cache.get_or_search("a", 2, &mut search).await.unwrap(),
+            [1, 2]
+        );
+        assert_eq!(
+            *cache.get_or_search("a", 3, &mut search).await.unwrap(),
+            [1, 2, 3, 4]
+        );
+        assert_eq!(
+            *cache.get_or_search("a", 10, &mut search).await.unwrap(),
+            [1, 2, 3, 4]
+        );
+        assert_eq!(
+            *cache.get_or_search("a", 10, &mut search).await.unwrap(),
+            [1, 2, 3, 4]
+        );
+        assert_eq!(searched, [0, 1, 2]);
+
+        let failed = cache.get_or_search("b", 1, |_| async { Err::<Vec<i32>, _>("down") });
+        assert_eq!(failed.await, Err("down"));
+
+        let cache = SearchCache::new(Duration::ZERO);
+        cache
+            .get_or_search("a", 1, |_| async { Ok::<_, ()>(vec![1]) })
+            .await
+            .unwrap();
+        let expired = cache.get_or_search("a", 1, |_| async { Ok::<_, ()>(vec![2]) });
+        assert_eq!(*expired.await.unwrap(), [2]);
     }
 }
@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use grammers_client::grammers_tl_types;
+use std::{
+    sync::atomic::{AtomicI64, Ordering},
+    time::SystemTime,
+};
+
+static LAST_ID: AtomicI64 = AtomicI64::new(0);
+
+/// Unique result id for results built without an explicit id
+pub(crate) fn generate_random_id() -> String {
+    if LAST_ID.load(Ordering::SeqCst) == 0 {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("system time is before epoch")
+            .as_nanos() as i64;
+
+        let _ = LAST_ID.compare_exchange(0, now, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
+    LAST_ID.fetch_add(1, Ordering::SeqCst).to_string()
+}
+
+pub(crate) fn web_document(
+    url: String,
+    mime_type: String,
+    attributes: Vec<grammers_tl_types::enums::DocumentAttribute>,
+) -> grammers_tl_types::enums::InputWebDocument {
+    grammers_tl_types::enums::InputWebDocument::Document(
+        grammers_tl_types::types::InputWebDocument {
+            url,
+            size: 0,
+            mime_type,
+            attributes,
+        },
+    )
+}
+
+/// Thumbnail from `url`, `image/jpeg` if `mime` is not set
+pub(crate) fn thumbnail(
+    url: Option<String>,
+    mime: Option<String>,
+) -> Option<grammers_tl_types::enums::InputWebDocument> {
+    Some(web_document(
+        url?,
+        mime.unwrap_or_else(|| "image/jpeg".to_string()),
+        vec![],
+    ))
+}
+
+/// Message that sends the media of the result with `text` as the caption
+pub(crate) fn media_message(
+    text: Option<String>,
+    reply_markup: Option<grammers_tl_types::enums::ReplyMarkup>,
+) -> grammers_tl_types::enums::InputBotInlineMessage {
+    grammers_tl_types::enums::InputBotInlineMessage::MediaAuto(
+        grammers_tl_types::types::InputBotInlineMessageMediaAuto {
+            invert_media: false,
+            message: text.unwrap_or_default(),
+            entities: None,
+            reply_markup,
+        },
+    )
+}
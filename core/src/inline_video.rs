@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::inline_result::{generate_random_id, media_message, thumbnail, web_document};
+use grammers_client::{grammers_tl_types, reply_markup::ReplyMarkup};
+
+/// ## InlineVideo
+/// Video result, Telegram requires a title and a thumbnail for it
+pub struct InlineVideo {
+    id: Option<String>,
+    url: String,
+    mime: Option<String>,
+    title: String,
+    description: Option<String>,
+    text: Option<String>,
+    thumbnail: String,
+    thumbnail_mime: Option<String>,
+    duration: Option<f64>,
+    size: Option<(i32, i32)>,
+    reply_markup: Option<grammers_tl_types::enums::ReplyMarkup>,
+}
+
+impl InlineVideo {
+    pub fn new(
+        url: impl Into<String>,
+        title: impl Into<String>,
+        thumbnail: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: None,
+            url: url.into(),
+            mime: None,
+            title: title.into(),
+            description: None,
+            text: None,
+            thumbnail: thumbnail.into(),
+            thumbnail_mime: None,
+            duration: None,
+            size: None,
+            reply_markup: None,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// MIME type of the video, `video/mp4` by default, `text/html` for pages with an embedded player
+    pub fn mime(mut self, mime: impl Into<String>) -> Self {
+        self.mime = Some(mime.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// MIME type of the thumbnail, `image/jpeg` by default
+    pub fn thumbnail_mime(mut self, mime: impl Into<String>) -> Self {
+        self.thumbnail_mime = Some(mime.into());
+        self
+    }
+
+    /// Duration in seconds
+    pub const fn duration(mut self, duration: f64) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    pub const fn size(mut self, width: i32, height: i32) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    pub fn reply_markup<RM: ReplyMarkup>(mut self, markup: &RM) -> Self {
+        self.reply_markup = Some(markup.to_reply_markup().raw);
+        self
+    }
+}
+
+impl From<InlineVideo> for grammers_tl_types::enums::InputBotInlineResult {
+    fn from(value: InlineVideo) -> Self {
+        let (w, h) = value.size.unwrap_or((0, 0));
+        Self::Result(grammers_tl_types::types::InputBotInlineResult {
+            id: value.id.unwrap_or_else(generate_random_id),
+            r#type: "video".to_string(),
+            title: Some(value.title),
+            description: value.description,
+            url: None,
+            thumb: thumbnail(Some(value.thumbnail), value.thumbnail_mime),
+            content: Some(web_document(
+                value.url,
+                value.mime.unwrap_or_else(|| "video/mp4".to_string()),
+                vec![grammers_tl_types::enums::DocumentAttribute::Video(
+                    grammers_tl_types::types::DocumentAttributeVideo {
+                        round_message: false,
+                        supports_streaming: true,
+                        nosound: false,
+                        duration: value.duration.unwrap_or(0.0),
+                        w,
+                        h,
+                        preload_prefix_size: None,
+                        video_start_ts: None,
+                        video_codec: None,
+                    },
+                )],
+            )),
+            send_message: media_message(value.text, value.reply_markup),
+        })
+    }
+}
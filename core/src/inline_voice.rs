@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::inline_result::{generate_random_id, media_message, web_document};
+use grammers_client::{grammers_tl_types, reply_markup::ReplyMarkup};
+
+/// ## InlineVoice
+/// Voice note result, the file should be OGG encoded with OPUS
+pub struct InlineVoice {
+    id: Option<String>,
+    url: String,
+    title: String,
+    text: Option<String>,
+    duration: Option<i32>,
+    waveform: Option<Vec<u8>>,
+    reply_markup: Option<grammers_tl_types::enums::ReplyMarkup>,
+}
+
+impl InlineVoice {
+    pub fn new(url: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            id: None,
+            url: url.into(),
+            title: title.into(),
+            text: None,
+            duration: None,
+            waveform: None,
+            reply_markup: None,
+        }
+    }
+
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Duration in seconds
+    pub const fn duration(mut self, duration: i32) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Waveform packed as 5-bit values, as Telegram clients send it
+    pub fn waveform(mut self, waveform: Vec<u8>) -> Self {
+        self.waveform = Some(waveform);
+        self
+    }
+
+    pub fn reply_markup<RM: ReplyMarkup>(mut self, markup: &RM) -> Self {
+        self.reply_markup = Some(markup.to_reply_markup().raw);
+        self
+    }
+}
+
+impl From<InlineVoice> for grammers_tl_types::enums::InputBotInlineResult {
+    fn from(value: InlineVoice) -> Self {
+        Self::Result(grammers_tl_types::types::InputBotInlineResult {
+            id: value.id.unwrap_or_else(generate_random_id),
+            r#type: "voice".to_string(),
+            title: Some(value.title),
+            description: None,
+            url: None,
+            thumb: None,
+            content: Some(web_document(
+                value.url,
+                "audio/ogg".to_string(),
+                vec![grammers_tl_types::enums::DocumentAttribute::Audio(
+                    grammers_tl_types::types::DocumentAttributeAudio {
+                        voice: true,
+                        duration: value.duration.unwrap_or(0),
+                        title: None,
+                        performer: None,
+                        waveform: value.waveform,
+                    },
+                )],
+            )),
+            send_message: media_message(value.text, value.reply_markup),
+        })
+    }
+}
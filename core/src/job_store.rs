@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+use std::time::SystemTime;
+
+/// ## JobRecord
+/// Last and next run of a job, kept so schedules do not reset on restart
+#[derive(Clone, Copy, Default, Debug)]
+pub struct JobRecord {
+    pub last_run: Option<SystemTime>,
+    pub next_run: Option<SystemTime>,
+}
+
+/// ## JobStore
+/// Place where job runs are recorded
+pub trait JobStore: Send + Sync {
+    fn load<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Option<JobRecord>>>;
+
+    fn save<'a>(&'a self, name: &'a str, record: JobRecord) -> BoxFuture<'a, anyhow::Result<()>>;
+}
+
+/// ## MemoryJobStore
+/// Keeps job runs in memory, schedules start over on restart
+#[derive(Default)]
+pub struct MemoryJobStore {
+    records: DashMap<String, JobRecord>,
+}
+
+impl MemoryJobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl JobStore for MemoryJobStore {
+    fn load<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Option<JobRecord>>> {
+        let record = self.records.get(name).map(|r| *r);
+        Box::pin(async move { Ok(record) })
+    }
+
+    fn save<'a>(&'a self, name: &'a str, record: JobRecord) -> BoxFuture<'a, anyhow::Result<()>> {
+        self.records.insert(name.to_owned(), record);
+        Box::pin(async move { Ok(()) })
+    }
+}
+
+/// ## SqliteJobStore
+/// Keeps job runs in the `jobs` table of a SQLite database
+#[cfg(feature = "sqlite")]
+pub struct SqliteJobStore {
+    pool: sqlx::SqlitePool,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteJobStore {
+    pub async fn new(pool: sqlx::SqlitePool) -> anyhow::Result<Self> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS jobs (name TEXT PRIMARY KEY, last_run INTEGER, next_run INTEGER)",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn to_unix(time: Option<SystemTime>) -> Option<i64> {
+    Some(time?.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs() as i64)
+}
+
+#[cfg(feature = "sqlite")]
+fn from_unix(secs: Option<i64>) -> Option<SystemTime> {
+    Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs? as u64))
+}
+
+#[cfg(feature = "sqlite")]
+impl JobStore for SqliteJobStore {
+    fn load<'a>(&'a self, name: &'a str) -> BoxFuture<'a, anyhow::Result<Option<JobRecord>>> {
+        Box::pin(async move {
+            let row = sqlx::query_as::<_, (Option<i64>, Option<i64>)>(
+                "SELECT last_run, next_run FROM jobs WHERE name = ?",
+            )
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+            Ok(row.map(|(last_run, next_run)| JobRecord {
+                last_run: from_unix(last_run),
+                next_run: from_unix(next_run),
+            }))
+        })
+    }
+
+    fn save<'a>(&'a self, name: &'a str, record: JobRecord) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO jobs (name, last_run, next_run) VALUES (?, ?, ?) ON CONFLICT (name) DO UPDATE SET last_run = excluded.last_run, next_run = excluded.next_run",
+            )
+            .bind(name)
+            .bind(to_unix(record.last_run))
+            .bind(to_unix(record.next_run))
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+}
@@ -1,56 +1,190 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2025 Myst33d <myst33d@gmail.com>
 
+pub mod args;
+pub mod callback;
+pub mod callback_query_ext;
+pub mod client;
+pub mod command;
+pub mod connection;
+pub mod conversation;
+pub mod conversation_store;
+pub mod error_reporter;
+#[cfg(test)]
+mod fake_client;
+pub mod handler;
+pub mod i18n;
+pub mod inline_article;
 pub mod inline_audio;
+pub mod inline_cached;
+pub mod inline_command;
+pub mod inline_document;
 pub mod inline_message_ext;
+pub mod inline_photo;
 pub mod inline_query;
+mod inline_result;
+pub mod inline_video;
+pub mod inline_voice;
+pub mod job_store;
+pub mod member;
+pub mod metrics;
+pub mod middleware;
+pub mod progress;
+pub mod role;
+mod router;
+pub mod scheduler;
+pub mod session_store;
+pub mod shutdown;
+pub mod user_store;
 
-use clokwerk::{AsyncScheduler, Interval};
+use args::{ArgSpec, FromArgs};
+use client::{
+    BotClient, IncomingCallbackQuery, IncomingInlineQuery, IncomingInlineSend, IncomingMessage,
+};
+use command::{CommandInfo, CommandOptions, CommandScope, PeerMenu};
+use connection::{Connection, ConnectionState, ErrorKind};
+use conversation::{ConversationKey, ConversationState, Conversations, Reply};
+use conversation_store::ConversationStore;
 use dashmap::DashMap;
+use error_reporter::ErrorReporter;
 use futures::future::BoxFuture;
 use grammers_client::{
-    Client, Config, Update,
-    client::bots::AuthorizationError,
+    Client, Config, InitParams, Update,
+    grammers_tl_types::enums::{self, InputBotInlineMessageId},
     session::Session,
-    types::{CallbackQuery, InlineQuery, InlineSend, Message, User},
+    types::MessageDeletion,
 };
-use regex::Regex;
-use std::{sync::Arc, time::Duration};
-
-type Fut = BoxFuture<'static, ()>;
-type MessageCallback<State> = fn(Context<State>, Message) -> Fut;
-type CallbackQueryCallback<State> = fn(Context<State>, CallbackQuery) -> Fut;
-type InlineQueryCallback<State> = fn(Context<State>, InlineQuery) -> Fut;
-type InlineSendCallback<State> = fn(Context<State>, InlineSend) -> Fut;
-type ContextCallback<State> = fn(Context<State>) -> Fut;
-
-type ComposeModuleFunc<State> = fn(MystbotCore<State>, &mut AsyncScheduler) -> MystbotCore<State>;
+use handler::{BoxedHandler, Handler};
+use i18n::{Arg, Catalog, Locales};
+use inline_article::InlineArticle;
+use inline_command::InlineCommandOptions;
+use inline_message_ext::InlineMessageExt;
+use job_store::{JobRecord, JobStore, MemoryJobStore};
+use member::MemberUpdate;
+use metrics::Metrics;
+use middleware::{Event, Flow};
+use role::{Role, Roles};
+use router::{Incoming, Route, Router};
+use scheduler::{JobData, JobOptions, Jobs, Schedule};
+use session_store::SessionStore;
+use shutdown::{PendingGuard, PendingInlineMessages};
+use std::{
+    net::SocketAddr,
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::sync::Notify;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{Instrument, Span, field};
+use user_store::{MemoryUserStore, UserStore};
 
-struct CommandData<State> {
-    regex: Regex,
-    func: Box<MessageCallback<State>>,
-}
+type BeforeCallback<State, C> =
+    Arc<dyn Fn(Context<State, C>, Event<C>) -> BoxFuture<'static, Flow> + Send + Sync>;
+type ConversationHandler<State, C> = BoxedHandler<State, (Reply<C>, ConversationState), C>;
 
 #[derive(Clone)]
-pub struct Context<State> {
-    pub client: Client,
+pub struct Context<State, C: BotClient = Client> {
+    pub client: C,
     pub state: State,
+    connection: Connection,
+    pending: PendingInlineMessages,
+    conversations: Conversations<C>,
+    jobs: Jobs,
+    locales: Locales,
+    language: String,
+    roles: Roles,
+    role: Role,
+    metrics: Metrics,
 }
 
-impl<State> Context<State> {
-    pub fn new(client: Client, state: State) -> Self {
-        Context { client, state }
+impl<State, C: BotClient> Context<State, C> {
+    /// Current state of the connection to Telegram
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection.get()
+    }
+
+    /// Mark inline message as pending until the guard is dropped, pending messages are edited to the shutdown notice if the bot stops before they are done
+    pub fn mark_pending(&self, message_id: InputBotInlineMessageId) -> PendingGuard {
+        self.pending.add(message_id, self.language.clone())
+    }
+
+    /// Conversation state of users, used to ask questions and to run multi-step conversations
+    pub fn conversations(&self) -> &Conversations<C> {
+        &self.conversations
+    }
+
+    /// Scheduled jobs, used to run them manually
+    pub fn jobs(&self) -> &Jobs {
+        &self.jobs
+    }
+
+    /// Message catalogs and languages chosen by users
+    pub fn locales(&self) -> &Locales {
+        &self.locales
+    }
+
+    /// Language of the user who caused the update, the default one in jobs
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Message `key` in the language of the user with placeholders filled from `args`
+    pub fn text(&self, key: &str, args: &[(&str, Arg)]) -> String {
+        self.locales.text(&self.language, key, args)
+    }
+
+    /// Roles of all users
+    pub fn roles(&self) -> &Roles {
+        &self.roles
+    }
+
+    /// Role of the user who caused the update, `Role::Owner` in jobs
+    pub const fn role(&self) -> Role {
+        self.role
+    }
+
+    /// Prometheus metrics, served if `MystbotCore::set_metrics_address` was called
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
     }
 }
 
-pub struct MystbotCore<State> {
-    me: User,
-    commands: DashMap<String, CommandData<State>>,
-    client: Client,
+pub struct MystbotCore<State, C: BotClient = Client> {
+    bot_id: i64,
+    bot_token: String,
+    session_store: Arc<dyn SessionStore>,
+    session_save_interval: Duration,
+    connection: Connection,
+    pending: PendingInlineMessages,
+    conversations: Conversations<C>,
+    conversation_handlers: DashMap<String, ConversationHandler<State, C>>,
+    locales: Locales,
+    roles: Roles,
+    metrics: Metrics,
+    metrics_address: Option<SocketAddr>,
+    jobs: Jobs,
+    job_data: DashMap<String, JobData<State, C>>,
+    job_store: Arc<dyn JobStore>,
+    shutdown_notice: Option<String>,
+    shutdown_timeout: Duration,
+    module: String,
+    help: Option<Arc<OnceLock<Vec<CommandInfo>>>>,
+    peer_menus: DashMap<i64, PeerMenu>,
+    peer_menu_store: Arc<dyn UserStore<PeerMenu>>,
+    router: Router<State, C>,
+    client: C,
     state: State,
-    callback_query: Option<CallbackQueryCallback<State>>,
-    inline_query: Option<InlineQueryCallback<State>>,
-    inline_send: Option<InlineSendCallback<State>>,
+    edited: Vec<BoxedHandler<State, (C::Message,), C>>,
+    deleted: Vec<BoxedHandler<State, (MessageDeletion,), C>>,
+    member_updates: Vec<BoxedHandler<State, (MemberUpdate,), C>>,
+    bot_membership: Vec<BoxedHandler<State, (MemberUpdate,), C>>,
+    raw: Vec<BoxedHandler<State, (enums::Update,), C>>,
+    error_reporter: ErrorReporter,
+    before: Vec<BeforeCallback<State, C>>,
+    after: Vec<BoxedHandler<State, (Event<C>, Duration), C>>,
 }
 
 impl<State: Send + Sync + Clone + 'static> MystbotCore<State> {
@@ -58,14 +192,24 @@ impl<State: Send + Sync + Clone + 'static> MystbotCore<State> {
         bot_token: &str,
         api_id: i32,
         api_hash: &str,
+        session_store: Arc<dyn SessionStore>,
         state: State,
-    ) -> Result<(Client, Self), AuthorizationError> {
+    ) -> anyhow::Result<(Client, Self)> {
+        let session = match session_store.load().await? {
+            Some(data) => {
+                Session::load(&data).map_err(|e| anyhow::anyhow!("failed to load session: {e}"))?
+            }
+            None => Session::new(),
+        };
+
         let client = grammers_client::Client::connect(Config {
-            session: Session::load_file_or_create("teobot.session")
-                .expect("failed to load session"),
+            session,
             api_id,
             api_hash: api_hash.to_owned(),
-            params: Default::default(),
+            params: InitParams {
+                reconnection_policy: &connection::Backoff,
+                ..Default::default()
+            },
         })
         .await?;
 
@@ -73,135 +217,1184 @@ impl<State: Send + Sync + Clone + 'static> MystbotCore<State> {
             client.bot_sign_in(bot_token).await?;
         }
 
-        client.session().save_to_file("teobot.session")?;
-        let me = client.get_me().await.unwrap();
+        session_store.save(client.session().save()).await?;
+        let me = client.get_me().await?;
 
         Ok((
             client.clone(),
-            Self {
-                me,
-                commands: DashMap::new(),
+            Self::new(
                 client,
+                me.id(),
+                me.username().map(str::to_owned),
+                bot_token.to_owned(),
+                session_store,
                 state,
-                callback_query: None,
-                inline_query: None,
-                inline_send: None,
-            },
+            ),
         ))
     }
+}
+
+impl<State: Send + Sync + Clone + 'static, C: BotClient> MystbotCore<State, C> {
+    /// Core for `client` signed in as the bot with `bot_id`, commands addressed to other bots than `username` are ignored
+    fn new(
+        client: C,
+        bot_id: i64,
+        username: Option<String>,
+        bot_token: String,
+        session_store: Arc<dyn SessionStore>,
+        state: State,
+    ) -> Self {
+        Self {
+            bot_id,
+            bot_token,
+            session_store,
+            session_save_interval: Duration::from_secs(300),
+            connection: Connection::default(),
+            pending: PendingInlineMessages::default(),
+            conversations: Conversations::default(),
+            conversation_handlers: DashMap::new(),
+            locales: Locales::default(),
+            roles: Roles::default(),
+            metrics: Metrics::default(),
+            metrics_address: None,
+            jobs: Jobs::default(),
+            job_data: DashMap::new(),
+            job_store: Arc::new(MemoryJobStore::new()),
+            shutdown_notice: None,
+            shutdown_timeout: Duration::from_secs(30),
+            module: "General".to_owned(),
+            help: None,
+            peer_menus: DashMap::new(),
+            peer_menu_store: Arc::new(MemoryUserStore::new()),
+            router: Router::new(username),
+            client,
+            state,
+            edited: vec![],
+            deleted: vec![],
+            member_updates: vec![],
+            bot_membership: vec![],
+            raw: vec![],
+            error_reporter: ErrorReporter::default(),
+            before: vec![],
+            after: vec![],
+        }
+    }
 
     /// Add new command to handler list
-    pub fn add_command(&mut self, command: impl Into<String>, handler: MessageCallback<State>) {
+    pub fn add_command(
+        &mut self,
+        command: impl Into<String>,
+        handler: impl Handler<State, (C::Message,), C>,
+    ) -> CommandOptions<'_, State, C> {
+        self.insert_command(command.into(), ArgSpec::new(), Arc::new(handler))
+    }
+
+    /// Add new command which arguments are parsed into `A`, on bad input the user gets the `usage` message instead
+    pub fn add_command_with_args<A: FromArgs + 'static>(
+        &mut self,
+        command: impl Into<String>,
+        handler: impl Handler<State, (C::Message, A), C>,
+    ) -> CommandOptions<'_, State, C> {
         let command = command.into();
-        self.commands.insert(
-            command.clone(),
-            CommandData {
-                regex: Regex::new(&format!(
-                    r"^(\/{})(@|)({}|)( |$)",
-                    command,
-                    self.me.username().unwrap(),
-                ))
-                .unwrap(),
-                func: Box::new(handler),
+        let spec = A::spec();
+        let name = command.clone();
+        self.insert_command(
+            command,
+            spec.clone(),
+            Arc::new(
+                move |context: Context<State, C>,
+                      message: C::Message|
+                      -> BoxFuture<'static, anyhow::Result<()>> {
+                    match spec.parse(message.text()).and_then(A::from_args) {
+                        Ok(args) => handler.call(context, (message, args)),
+                        Err(_) => {
+                            let usage = spec.usage(&name, |key| context.text(key, &[]));
+                            let usage = context.text("usage", &[("usage", usage.into())]);
+                            Box::pin(async move {
+                                context.client.reply(&message, usage).await?;
+                                Ok(())
+                            })
+                        }
+                    }
+                },
+            ),
+        )
+    }
+
+    /// Add built-in command `/help` that lists commands the user can run with descriptions grouped by module,
+    /// `header` and the descriptions are message keys translated to the language of the user
+    pub fn add_help(
+        &mut self,
+        header: impl Into<String>,
+        description: impl Into<String>,
+    ) -> CommandOptions<'_, State, C> {
+        let header = header.into();
+        let commands = Arc::new(OnceLock::new());
+        self.help = Some(commands.clone());
+        self.add_command(
+            "help",
+            move |context: Context<State, C>, message: C::Message| {
+                let commands = commands
+                    .get()
+                    .into_iter()
+                    .flatten()
+                    .filter(|info| context.role() >= info.role);
+                let text = command::help_text(&header, commands, |key| context.text(key, &[]));
+                async move {
+                    context.client.reply(&message, text).await?;
+                    Ok(())
+                }
             },
-        );
+        )
+        .description(description)
+    }
+
+    /// Add built-in command `/cancel` that ends the conversation of the user in the chat, `reply` is a message key
+    pub fn add_cancel(
+        &mut self,
+        reply: impl Into<String>,
+        description: impl Into<String>,
+    ) -> CommandOptions<'_, State, C> {
+        let reply = reply.into();
+        self.add_command(
+            "cancel",
+            move |context: Context<State, C>, message: C::Message| {
+                let reply = reply.clone();
+                async move {
+                    if let Some(key) = ConversationKey::from_message(&message) {
+                        context.conversations().finish(key).await?;
+                    }
+                    let text = context.text(&reply, &[]);
+                    context.client.reply(&message, text).await?;
+                    Ok(())
+                }
+            },
+        )
+        .description(description)
+    }
+
+    /// Set module name or its message key that commands registered after this call are grouped under in `/help`
+    pub fn set_module(&mut self, module: impl Into<String>) {
+        self.module = module.into();
+    }
+
+    fn insert_command(
+        &mut self,
+        command: String,
+        args: ArgSpec,
+        func: BoxedHandler<State, (C::Message,), C>,
+    ) -> CommandOptions<'_, State, C> {
+        self.router
+            .add_command(command, args, self.module.clone(), func)
+    }
+
+    /// Registered commands in registration order
+    pub fn commands(&self) -> Vec<CommandInfo> {
+        self.router.commands()
+    }
+
+    /// Add callback query handler for buttons whose data was built with `callback::encode(prefix, ...)`, registering the same prefix again replaces the old handler
+    pub fn add_callback(
+        &mut self,
+        prefix: impl Into<String>,
+        handler: impl Handler<State, (C::CallbackQuery,), C>,
+    ) {
+        self.router.add_callback(prefix.into(), Arc::new(handler));
+    }
+
+    /// Add handler for replies in conversations started with `Conversations::set` under `name`,
+    /// replies are messages that are not commands and button presses without a callback handler
+    pub fn add_conversation(
+        &mut self,
+        name: impl Into<String>,
+        handler: impl Handler<State, (Reply<C>, ConversationState), C>,
+    ) {
+        self.conversation_handlers
+            .insert(name.into(), Arc::new(handler));
+    }
+
+    /// Set where conversations are kept, in memory by default
+    pub fn set_conversation_store(&mut self, store: Arc<dyn ConversationStore>) {
+        self.conversations.store = store;
+    }
+
+    /// Set how long a conversation lasts without replies, 10 minutes by default
+    pub const fn set_conversation_timeout(&mut self, timeout: Duration) {
+        self.conversations.timeout = timeout;
+    }
+
+    /// Add inline command, queries starting with `command` are passed to `query_handler` with the rest of the words as arguments,
+    /// chosen results with ids built by `inline_command::result_id(command, ...)` are passed to `send_handler` with the id parts
+    pub fn add_inline_command(
+        &mut self,
+        command: impl Into<String>,
+        query_handler: impl Handler<State, (C::InlineQuery, Vec<String>), C>,
+        send_handler: impl Handler<State, (C::InlineSend, Vec<String>), C>,
+    ) -> InlineCommandOptions<'_, State, C> {
+        self.router.add_inline_command(
+            command.into(),
+            Arc::new(query_handler),
+            Arc::new(send_handler),
+        )
+    }
+
+    /// Set handler for inline queries that do not match any inline command, there can be only one handler, if you call this function again with another handler it will replace the old one
+    pub fn set_inline_fallback(&mut self, handler: impl Handler<State, (C::InlineQuery,), C>) {
+        self.router.set_inline_fallback(Arc::new(handler));
+    }
+
+    /// Add handler for edited messages, edits of commands registered with `handle_edits` go to the command instead
+    pub fn add_message_edited(&mut self, handler: impl Handler<State, (C::Message,), C>) {
+        self.edited.push(Arc::new(handler));
+    }
+
+    /// Add handler for deleted messages
+    pub fn add_message_deleted(&mut self, handler: impl Handler<State, (MessageDeletion,), C>) {
+        self.deleted.push(Arc::new(handler));
+    }
+
+    /// Add handler for users other than the bot joining or leaving chats
+    pub fn add_member_update(&mut self, handler: impl Handler<State, (MemberUpdate,), C>) {
+        self.member_updates.push(Arc::new(handler));
+    }
+
+    /// Add handler for the bot being added to or removed from chats
+    pub fn add_bot_membership(&mut self, handler: impl Handler<State, (MemberUpdate,), C>) {
+        self.bot_membership.push(Arc::new(handler));
+    }
+
+    /// Add handler for raw updates that have no dedicated handler
+    pub fn add_raw(&mut self, handler: impl Handler<State, (enums::Update,), C>) {
+        self.raw.push(Arc::new(handler));
+    }
+
+    /// Add middleware that runs before every handler in registration order, returning `Flow::Stop` drops the update
+    pub fn add_before<Fut: Future<Output = Flow> + Send + 'static>(
+        &mut self,
+        handler: impl Fn(Context<State, C>, Event<C>) -> Fut + Send + Sync + 'static,
+    ) {
+        self.before.push(Arc::new(move |context, event| {
+            Box::pin(handler(context, event))
+        }));
+    }
+
+    /// Add middleware that runs after every handler in registration order, receives the time it took to handle the update
+    pub fn add_after(&mut self, handler: impl Handler<State, (Event<C>, Duration), C>) {
+        self.after.push(Arc::new(handler));
+    }
+
+    /// Set what happens with errors returned by handlers
+    pub fn set_error_reporter(&mut self, reporter: ErrorReporter) {
+        self.error_reporter = reporter;
     }
 
-    /// Set callback query handler, there can be only one handler, if you call this function again with another handler it will replace the old one
-    pub fn set_callback_query(&mut self, handler: CallbackQueryCallback<State>) {
-        self.callback_query = Some(handler);
+    /// Set message key that pending inline messages are edited to when the bot shuts down before they are done
+    pub fn set_shutdown_notice(&mut self, text: impl Into<String>) {
+        self.shutdown_notice = Some(text.into());
     }
 
-    /// Set inline query handler, there can be only one handler, if you call this function again with another handler it will replace the old one
-    pub fn set_inline_query(&mut self, handler: InlineQueryCallback<State>) {
-        self.inline_query = Some(handler);
+    /// Set how long to wait for running handlers and jobs on shutdown, 30 seconds by default
+    pub const fn set_shutdown_timeout(&mut self, timeout: Duration) {
+        self.shutdown_timeout = timeout;
     }
 
-    /// Set inline send handler, there can be only one handler, if you call this function again with another handler it will replace the old one
-    pub fn set_inline_send(&mut self, handler: InlineSendCallback<State>) {
-        self.inline_send = Some(handler);
+    /// Set how often the session is saved while the bot is running, 5 minutes by default
+    pub const fn set_session_save_interval(&mut self, interval: Duration) {
+        self.session_save_interval = interval;
     }
 
     /// Composable module registration
-    pub fn register(self, func: ComposeModuleFunc<State>, scheduler: &mut AsyncScheduler) -> Self {
-        func(self, scheduler)
+    pub fn register(self, func: impl FnOnce(Self) -> Self) -> Self {
+        func(self)
     }
 
-    /// Schedule a function to run on intervals
-    pub fn schedule_every(
-        &self,
-        scheduler: &mut AsyncScheduler,
-        ival: Interval,
-        func: ContextCallback<State>,
+    /// Add job that runs on `schedule`, runs are skipped while the previous one is still going unless overlap is allowed
+    pub fn add_job(
+        &mut self,
+        name: impl Into<String>,
+        schedule: Schedule,
+        handler: impl Handler<State, (), C>,
+    ) -> JobOptions<'_, State, C> {
+        let name = name.into();
+        let trigger = Arc::new(Notify::new());
+        self.jobs.triggers.insert(name.clone(), trigger.clone());
+        self.job_data.insert(
+            name.clone(),
+            JobData {
+                schedule,
+                jitter: Duration::ZERO,
+                allow_overlap: false,
+                func: Arc::new(handler),
+                running: Arc::new(AtomicBool::new(false)),
+                trigger,
+            },
+        );
+        JobOptions {
+            data: self.job_data.get_mut(&name).unwrap(),
+        }
+    }
+
+    /// Set where job runs are recorded, in memory by default
+    pub fn set_job_store(&mut self, store: Arc<dyn JobStore>) {
+        self.job_store = store;
+    }
+
+    /// Prometheus metrics, used by modules to set initial values before the bot starts
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Serve Prometheus metrics at `http://address/metrics`, there is no endpoint by default
+    pub const fn set_metrics_address(&mut self, address: SocketAddr) {
+        self.metrics_address = Some(address);
+    }
+
+    /// Set where roles granted with `/grant` are kept, in memory by default
+    pub fn set_role_store(&mut self, store: Arc<dyn UserStore<Role>>) {
+        self.roles.store = store;
+    }
+
+    /// Set where command menus of owners and admins are kept so they can be reset after a restart, in memory by default
+    pub fn set_peer_menu_store(&mut self, store: Arc<dyn UserStore<PeerMenu>>) {
+        self.peer_menu_store = store;
+    }
+
+    /// Give the user a role that can not be changed with commands, used for roles from the config
+    pub fn set_role(&mut self, user_id: i64, role: Role) {
+        self.roles.set_fixed(user_id, role);
+    }
+
+    /// Add built-in admin commands `/grant <user_id> <role>`, `/revoke <user_id>` and `/roles`
+    pub fn add_role_commands(
+        &mut self,
+        grant_description: impl Into<String>,
+        revoke_description: impl Into<String>,
+        roles_description: impl Into<String>,
     ) {
-        let context = Context::new(self.client.clone(), self.state.clone());
-        scheduler.every(ival).run(move || {
-            let context = context.clone();
-            async move {
-                let _ = func(context).await;
-            }
-        });
+        self.add_command_with_args("grant", role::grant)
+            .description(grant_description)
+            .role(Role::Admin);
+        self.add_command_with_args("revoke", role::revoke)
+            .description(revoke_description)
+            .role(Role::Admin);
+        self.add_command("roles", role::list)
+            .description(roles_description)
+            .role(Role::Admin);
+    }
+
+    /// Add messages of `language`, a language code like `en` or `ru`
+    pub fn add_catalog(&mut self, language: impl Into<String>, catalog: Catalog) {
+        self.locales.add_catalog(language.into(), catalog);
+    }
+
+    /// Set language used when the language of the user has no catalog, `en` by default
+    pub fn set_default_language(&mut self, language: impl Into<String>) {
+        self.locales.default_language = language.into();
+    }
+
+    /// Set where languages chosen by users are kept, in memory by default
+    pub fn set_language_store(&mut self, store: Arc<dyn UserStore<String>>) {
+        self.locales.store = store;
     }
 }
 
-/// Start bot
-pub async fn run<S: Sync + Send + Clone + 'static>(
-    app: Arc<MystbotCore<S>>,
-    mut scheduler: AsyncScheduler,
-) {
-    tokio::spawn(async move {
-        loop {
-            scheduler.run_pending().await;
-            tokio::time::sleep(Duration::from_millis(100)).await;
+impl<State: Send + Sync + Clone + 'static, C: BotClient> MystbotCore<State, C> {
+    fn context(&self) -> Context<State, C> {
+        Context {
+            client: self.client.clone(),
+            state: self.state.clone(),
+            connection: self.connection.clone(),
+            pending: self.pending.clone(),
+            conversations: self.conversations.clone(),
+            jobs: self.jobs.clone(),
+            locales: self.locales.clone(),
+            language: self.locales.default_language.clone(),
+            roles: self.roles.clone(),
+            role: Role::Owner,
+            metrics: self.metrics.clone(),
         }
-    });
+    }
 
-    loop {
-        let app = app.clone();
-        let context = Context::new(app.client.clone(), app.state.clone());
+    /// Context for handlers of `event`, in the language and with the role of its sender
+    async fn event_context(&self, event: &Event<C>) -> Context<State, C> {
+        let mut context = self.context();
+        match event.sender_id() {
+            Some(user_id) => {
+                context.language = self
+                    .locales
+                    .language(user_id, event.lang_code().as_deref())
+                    .await;
+                context.role = self.roles.role(user_id).await;
+            }
+            None => context.role = Role::User,
+        }
+        context
+    }
 
-        match app.client.next_update().await.unwrap() {
-            Update::NewMessage(message) => {
-                tokio::spawn(async move {
-                    if message.outgoing() {
-                        return;
-                    }
-                    for multi in app.commands.iter() {
-                        let Some(caps) = multi.regex.captures(message.text()) else {
-                            continue;
-                        };
-                        if caps[2].is_empty() || (!caps[2].is_empty() && !caps[3].is_empty()) {
-                            if caps[1][1..] != *multi.key() {
-                                continue;
-                            }
-                            (multi.func)(context.clone(), message.clone()).await;
-                            break;
-                        }
-                    }
+    /// Fill `/help` command list and push commands that every user can run to the Telegram command menu in every language,
+    /// users whose language has no catalog get the menu in the default language
+    async fn publish_commands(&self) {
+        let commands = self.commands();
+
+        if let Some(help) = &self.help {
+            let _ = help.set(commands.clone());
+        }
+
+        let menus = std::iter::once((String::new(), self.locales.default_language.clone()))
+            .chain(self.locales.languages().into_iter().map(|l| (l.clone(), l)));
+        for (lang_code, language) in menus {
+            for scope in [
+                CommandScope::All,
+                CommandScope::Private,
+                CommandScope::Group,
+            ] {
+                let request = command::set_bot_commands(scope, &lang_code, &commands, |key| {
+                    self.locales.text(&language, key, &[])
                 });
+                if let Err(e) = self.client.set_bot_commands(request).await {
+                    tracing::warn!(lang_code, error = %e, "failed to set bot commands");
+                }
             }
-            Update::CallbackQuery(query) => {
-                tokio::spawn(async move {
-                    if let Some(func) = app.callback_query {
-                        let _ = func(context.clone(), query).await;
-                    }
-                });
+        }
+    }
+
+    /// Give owners and admins a menu with the commands of their role in the private chat with the bot.
+    /// It is updated on their messages when their role or language changes, and removed when they are no longer admins
+    async fn update_peer_menu(&self, context: &Context<State, C>, message: &C::Message) {
+        let Some(peer) = message.private_chat() else {
+            return;
+        };
+        let menu = PeerMenu {
+            role: context.role,
+            language: context.language.clone(),
+            peer,
+        };
+        match self.peer_menus.get(&peer.id).as_deref() {
+            Some(published) if *published == menu => return,
+            None if menu.role < Role::Admin => return,
+            _ => {}
+        }
+        self.publish_peer_menu(peer.id, menu).await;
+    }
+
+    /// Bring menus published for owners and admins before the restart up to date with the commands and roles,
+    /// menus of users who are no longer admins are reset
+    async fn restore_peer_menus(&self) {
+        let menus = match self.peer_menu_store.list().await {
+            Ok(menus) => menus,
+            Err(e) => {
+                tracing::warn!(error = ?e, "failed to load bot commands of users");
+                return;
             }
-            Update::InlineQuery(query) => {
-                tokio::spawn(async move {
-                    if let Some(func) = app.inline_query {
-                        let _ = func(context.clone(), query).await;
+        };
+        for (user_id, menu) in menus {
+            let role = self.roles.role(user_id).await;
+            self.publish_peer_menu(user_id, PeerMenu { role, ..menu })
+                .await;
+        }
+    }
+
+    /// Set the menu of the private chat with the user to the commands of `menu.role`, or reset it below `Role::Admin`
+    async fn publish_peer_menu(&self, user_id: i64, menu: PeerMenu) {
+        let peer = menu.peer.to_input_peer();
+        let result = if menu.role < Role::Admin {
+            let request = command::reset_peer_commands(peer);
+            self.client.reset_bot_commands(request).await
+        } else {
+            let commands = self.commands();
+            let request = command::set_peer_commands(peer, menu.role, &commands, |key| {
+                self.locales.text(&menu.language, key, &[])
+            });
+            self.client.set_bot_commands(request).await
+        };
+        if let Err(e) = result {
+            tracing::warn!(user_id, error = %e, "failed to set bot commands of the user");
+            return;
+        }
+
+        let saved = if menu.role < Role::Admin {
+            self.peer_menus.remove(&user_id);
+            self.peer_menu_store.remove(user_id).await
+        } else {
+            self.peer_menus.insert(user_id, menu.clone());
+            self.peer_menu_store.save(user_id, menu).await
+        };
+        if let Err(e) = saved {
+            tracing::warn!(user_id, error = ?e, "failed to save bot commands of the user");
+        }
+    }
+
+    /// Wait for the scheduled or manually triggered runs of the job and start them until the token is cancelled
+    async fn run_job(
+        self: Arc<Self>,
+        name: String,
+        job: JobData<State, C>,
+        tracker: TaskTracker,
+        token: CancellationToken,
+    ) {
+        let mut record = match self.job_store.load(&name).await {
+            Ok(record) => record.unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!(job = %name, error = ?e, "failed to load job");
+                JobRecord::default()
+            }
+        };
+
+        while let Some(next_run) = record.next_run.or_else(|| job.next_run(SystemTime::now())) {
+            record.next_run = Some(next_run);
+            self.save_job(&name, record).await;
+
+            let delay = next_run
+                .duration_since(SystemTime::now())
+                .unwrap_or_default();
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(delay) => record.next_run = None,
+                _ = job.trigger.notified() => {}
+            }
+
+            if !job.allow_overlap && job.running.swap(true, Ordering::SeqCst) {
+                tracing::warn!(job = %name, "job is still running, skipping");
+                continue;
+            }
+            record.last_run = Some(SystemTime::now());
+
+            let app = self.clone();
+            let job = job.clone();
+            let name = name.clone();
+            let span = tracing::info_span!("job", name = %name, duration_ms = field::Empty);
+            tracker.spawn(
+                async move {
+                    let labels = [("job", name.as_str())];
+                    let started = Instant::now();
+                    let context = app.context();
+                    if let Err(e) = job.func.call(context.clone(), ()).await {
+                        app.metrics.inc("mystbot_job_errors_total", &labels);
+                        app.error_reporter.report(&context, None, &e).await;
                     }
-                });
+                    let elapsed = started.elapsed();
+                    app.metrics
+                        .observe("mystbot_job_duration_seconds", &labels, elapsed);
+                    Span::current().record("duration_ms", elapsed.as_millis() as u64);
+                    tracing::info!("job finished");
+                    if !job.allow_overlap {
+                        job.running.store(false, Ordering::SeqCst);
+                    }
+                }
+                .instrument(span),
+            );
+        }
+    }
+
+    async fn save_job(&self, name: &str, record: JobRecord) {
+        if let Err(e) = self.job_store.save(name, record).await {
+            tracing::warn!(job = %name, error = ?e, "failed to save job");
+        }
+    }
+
+    async fn handle(&self, context: Context<State, C>, event: Event<C>) {
+        let kind = event.kind();
+        self.metrics.inc("mystbot_updates_total", &[("kind", kind)]);
+
+        if let Event::Message(message) = &event {
+            self.update_peer_menu(&context, message).await;
+        }
+
+        // Membership changes are still handled so modules can clean up after chats the bot left
+        let membership = matches!(event, Event::MemberUpdate(_) | Event::BotMembership(_));
+        if context.role == Role::Banned && !membership {
+            return;
+        }
+
+        for func in self.before.iter() {
+            if func(context.clone(), event.clone()).await == Flow::Stop {
+                return;
+            }
+        }
+
+        let started = Instant::now();
+        let mut handler = None;
+        let result = self
+            .dispatch(context.clone(), event.clone(), &mut handler)
+            .await;
+        let elapsed = started.elapsed();
+
+        let handler = handler.unwrap_or_else(|| "none".to_owned());
+        let labels = [("kind", kind), ("handler", handler.as_str())];
+        self.metrics
+            .observe("mystbot_handler_duration_seconds", &labels, elapsed);
+        Span::current().record("duration_ms", elapsed.as_millis() as u64);
+        tracing::info!("update handled");
+
+        if let Err(e) = result {
+            self.metrics.inc("mystbot_handler_errors_total", &labels);
+            self.error_reporter.report(&context, Some(&event), &e).await;
+        }
+
+        for func in self.after.iter() {
+            if let Err(e) = func.call(context.clone(), (event.clone(), elapsed)).await {
+                self.error_reporter.report(&context, Some(&event), &e).await;
+            }
+        }
+    }
+
+    /// Run the handler of the event, its name is stored in `handler` for logs and metrics
+    async fn dispatch(
+        &self,
+        context: Context<State, C>,
+        event: Event<C>,
+        handler: &mut Option<String>,
+    ) -> anyhow::Result<()> {
+        let incoming = match &event {
+            Event::Message(message) => Some(Incoming::Message(message.text())),
+            Event::MessageEdited(message) => Some(Incoming::MessageEdited(message.text())),
+            Event::CallbackQuery(query) => Some(Incoming::CallbackQuery(query.data())),
+            Event::InlineQuery(query) => Some(Incoming::InlineQuery(query.text())),
+            Event::InlineSend(send) => Some(Incoming::InlineSend(send.result_id())),
+            _ => None,
+        };
+        let route = match incoming {
+            Some(incoming) => self.router.route(incoming, context.role),
+            None => Route::Ignore,
+        };
+
+        match (route, event) {
+            (
+                Route::Command {
+                    handler: name,
+                    func,
+                },
+                Event::Message(message) | Event::MessageEdited(message),
+            ) => {
+                set_handler(handler, name);
+                func.call(context, (message,)).await
+            }
+            (
+                Route::Callback {
+                    handler: name,
+                    func,
+                },
+                Event::CallbackQuery(query),
+            ) => {
+                set_handler(handler, name);
+                func.call(context, (query,)).await
+            }
+            (
+                Route::InlineQuery {
+                    handler: name,
+                    func,
+                    args,
+                },
+                Event::InlineQuery(query),
+            ) => {
+                set_handler(handler, name);
+                func.call(context, (query, args)).await
+            }
+            (Route::InlineFallback { func }, Event::InlineQuery(query)) => {
+                set_handler(handler, "inline_fallback".to_owned());
+                func.call(context, (query,)).await
+            }
+            (
+                Route::InlineSend {
+                    handler: name,
+                    func,
+                    args,
+                },
+                Event::InlineSend(send),
+            ) => {
+                set_handler(handler, name);
+                func.call(context, (send, args)).await
+            }
+            (Route::Denied { handler: name }, Event::Message(message)) => {
+                set_handler(handler, name);
+                let text = context.text("roles.denied", &[]);
+                context.client.reply(&message, text).await?;
+                Ok(())
+            }
+            (Route::Denied { handler: name }, Event::InlineQuery(query)) => {
+                set_handler(handler, name);
+                let text = context.text("roles.denied", &[]);
+                inline_query::InlineQuery::new(query, context.client.clone())
+                    .answer([InlineArticle::new(text)])
+                    .send()
+                    .await?;
+                Ok(())
+            }
+            (Route::Edited, Event::MessageEdited(message)) => {
+                set_handler(handler, "message_edited".to_owned());
+                call_all(&self.edited, context, message).await
+            }
+            (Route::Conversation, Event::Message(message)) => {
+                self.continue_conversation(context, Reply::Message(message), handler)
+                    .await
+            }
+            (Route::Conversation, Event::CallbackQuery(query)) => {
+                self.continue_conversation(context, Reply::CallbackQuery(query), handler)
+                    .await
+            }
+            (_, Event::MessageDeleted(deletion)) => {
+                set_handler(handler, "message_deleted".to_owned());
+                call_all(&self.deleted, context, deletion).await
+            }
+            (_, Event::MemberUpdate(update)) => {
+                set_handler(handler, "member_update".to_owned());
+                call_all(&self.member_updates, context, update).await
+            }
+            (_, Event::BotMembership(update)) => {
+                set_handler(handler, "bot_membership".to_owned());
+                call_all(&self.bot_membership, context, update).await
+            }
+            (_, Event::Raw(update)) => {
+                set_handler(handler, "raw".to_owned());
+                call_all(&self.raw, context, update).await
             }
-            Update::InlineSend(send) => {
-                tokio::spawn(async move {
-                    if let Some(func) = app.inline_send {
-                        let _ = func(context.clone(), send).await;
+            _ => Ok(()),
+        }
+    }
+
+    async fn continue_conversation(
+        &self,
+        context: Context<State, C>,
+        reply: Reply<C>,
+        handler: &mut Option<String>,
+    ) -> anyhow::Result<()> {
+        let Some((state, reply)) = self.conversations.route(reply).await? else {
+            return Ok(());
+        };
+        let Some(func) = self
+            .conversation_handlers
+            .get(&state.name)
+            .map(|f| f.clone())
+        else {
+            return Ok(());
+        };
+        set_handler(handler, format!("conversation:{}", state.name));
+        func.call(context, (reply, state)).await
+    }
+}
+
+impl<State: Send + Sync + Clone + 'static> MystbotCore<State> {
+    async fn save_session(&self) {
+        if let Err(e) = self.session_store.save(self.client.session().save()).await {
+            tracing::warn!(error = ?e, "failed to save session");
+        }
+    }
+
+    /// Wait for the next update, reconnecting and signing in again until it succeeds
+    async fn next_update(&self) -> Update {
+        let mut attempt = 0;
+        loop {
+            let error = match self.client.next_update().await {
+                Ok(update) => {
+                    self.connection.set(ConnectionState::Connected);
+                    return update;
+                }
+                Err(e) => e,
+            };
+
+            match connection::classify(&error) {
+                ErrorKind::Transient => {
+                    tracing::warn!(%error, "failed to receive updates");
+                    self.connection.set(ConnectionState::Reconnecting);
+                }
+                ErrorKind::Unauthorized => {
+                    tracing::error!(%error, "session was invalidated");
+                    self.connection.set(ConnectionState::Unauthorized);
+                    match self.client.bot_sign_in(&self.bot_token).await {
+                        Ok(_) => {
+                            self.save_session().await;
+                            self.connection.set(ConnectionState::Connected);
+                            continue;
+                        }
+                        Err(e) => tracing::error!(error = %e, "failed to sign in"),
                     }
-                });
+                }
             }
-            _ => {}
+
+            tokio::time::sleep(connection::backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Remember the handler of the update for metrics and name it in the span
+fn set_handler(handler: &mut Option<String>, name: String) {
+    Span::current().record("handler", name.as_str());
+    *handler = Some(name);
+}
+
+/// Call every handler in registration order, stops at the first error
+async fn call_all<State: Clone + 'static, C: BotClient, T: Clone + 'static>(
+    handlers: &[BoxedHandler<State, (T,), C>],
+    context: Context<State, C>,
+    value: T,
+) -> anyhow::Result<()> {
+    for func in handlers {
+        func.call(context.clone(), (value.clone(),)).await?;
+    }
+    Ok(())
+}
+
+/// Start bot, returns after SIGINT or SIGTERM once running handlers and jobs are done or the shutdown timeout expires
+pub async fn run<S: Sync + Send + Clone + 'static>(app: Arc<MystbotCore<S>>) {
+    app.publish_commands().await;
+    app.restore_peer_menus().await;
+
+    let tracker = TaskTracker::new();
+    let token = CancellationToken::new();
+
+    let jobs: Vec<_> = app
+        .job_data
+        .iter()
+        .map(|job| (job.key().clone(), job.value().clone()))
+        .collect();
+    for (name, job) in jobs {
+        tracker.spawn(
+            app.clone()
+                .run_job(name, job, tracker.clone(), token.clone()),
+        );
+    }
+
+    if let Some(address) = app.metrics_address {
+        tracker.spawn(metrics::serve(app.metrics.clone(), address, token.clone()));
+    }
+
+    {
+        let app = app.clone();
+        let token = token.clone();
+        tracker.spawn(async move {
+            let mut interval = tokio::time::interval(app.session_save_interval);
+            interval.tick().await;
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = interval.tick() => app.save_session().await,
+                }
+            }
+        });
+    }
+
+    let signal = shutdown::signal();
+    tokio::pin!(signal);
+
+    loop {
+        let app = app.clone();
+
+        let update = tokio::select! {
+            update = app.next_update() => update,
+            _ = &mut signal => break,
+        };
+
+        let event = match update {
+            Update::NewMessage(message) if !message.outgoing() => Event::Message(message),
+            Update::CallbackQuery(query) => Event::CallbackQuery(query),
+            Update::InlineQuery(query) => Event::InlineQuery(query),
+            Update::InlineSend(send) => Event::InlineSend(send),
+            Update::MessageEdited(message) if !message.outgoing() => Event::MessageEdited(message),
+            Update::MessageDeleted(deletion) => Event::MessageDeleted(deletion),
+            Update::Raw(update) => match MemberUpdate::from_raw(&update) {
+                Some(member) if member.user_id == app.bot_id => Event::BotMembership(member),
+                Some(member) => Event::MemberUpdate(member),
+                None => Event::Raw(update),
+            },
+            _ => continue,
+        };
+
+        let span = tracing::info_span!(
+            "update",
+            kind = event.kind(),
+            user_id = event.sender_id(),
+            chat_id = event.chat_id(),
+            handler = field::Empty,
+            duration_ms = field::Empty,
+        );
+        tracker.spawn(
+            async move {
+                let context = app.event_context(&event).await;
+                app.handle(context, event).await;
+            }
+            .instrument(span),
+        );
+    }
+
+    tracing::info!("shutting down");
+    token.cancel();
+    tracker.close();
+    if tokio::time::timeout(app.shutdown_timeout, tracker.wait())
+        .await
+        .is_err()
+    {
+        tracing::warn!("timed out waiting for running tasks");
+    }
+
+    let pending = app.pending.drain();
+    if let Some(notice) = &app.shutdown_notice {
+        for (message_id, language) in pending {
+            let text = app.locales.text(&language, notice, &[]);
+            let _ = app
+                .client
+                .edit_inline_message_ext(message_id, text.as_str())
+                .await;
         }
     }
+
+    app.save_session().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fake_client::{
+        Call, FakeCallbackQuery, FakeClient, FakeInlineQuery, FakeInlineSend, FakeMessage,
+    };
+    use grammers_client::{session::PackedType, types::PackedChat};
+    use session_store::FileSessionStore;
+
+    type TestContext = Context<(), FakeClient>;
+
+    const OWNER: i64 = 10;
+    const USER: i64 = 20;
+    const GROUP: i64 = -30;
+    const ADMIN_CHAT: i64 = -40;
+
+    fn app() -> MystbotCore<(), FakeClient> {
+        let mut app = MystbotCore::new(
+            FakeClient::default(),
+            1,
+            Some("MystBot".to_owned()),
+            String::new(),
+            Arc::new(FileSessionStore::new("unused")),
+            (),
+        );
+        app.set_role(OWNER, Role::Owner);
+        app
+    }
+
+    async fn handle(app: &MystbotCore<(), FakeClient>, event: Event<FakeClient>) -> Vec<Call> {
+        let context = app.event_context(&event).await;
+        app.handle(context, event).await;
+        app.client.take_calls()
+    }
+
+    fn reply(chat_id: i64, text: &str) -> Call {
+        Call::Reply {
+            chat_id,
+            text: text.to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn commands() {
+        let mut app = app();
+        app.add_command(
+            "ping",
+            |context: TestContext, message: FakeMessage| async move {
+                context.client.reply(&message, "pong".to_owned()).await?;
+                Ok(())
+            },
+        );
+        app.add_command("secret", |_: TestContext, _: FakeMessage| async { Ok(()) })
+            .role(Role::Admin);
+
+        let ping = FakeMessage::group(GROUP, USER, "/ping@MystBot");
+        assert_eq!(
+            handle(&app, Event::Message(ping)).await,
+            [reply(GROUP, "pong")]
+        );
+        let other_bot = FakeMessage::group(GROUP, USER, "/ping@OtherBot");
+        assert_eq!(handle(&app, Event::Message(other_bot)).await, []);
+        let secret = FakeMessage::group(GROUP, USER, "/secret");
+        assert_eq!(
+            handle(&app, Event::Message(secret)).await,
+            [reply(GROUP, "You are not allowed to do this")]
+        );
+    }
+
+    #[tokio::test]
+    async fn role_commands() {
+        let mut app = app();
+        app.add_role_commands("commands.grant", "commands.revoke", "commands.roles");
+
+        let grant = FakeMessage::group(GROUP, OWNER, "/grant 20 admin");
+        assert_eq!(
+            handle(&app, Event::Message(grant)).await,
+            [reply(GROUP, "20 is now admin")]
+        );
+        let grant = FakeMessage::group(GROUP, OWNER, "/grant twenty admin");
+        let calls = handle(&app, Event::Message(grant)).await;
+        assert!(
+            matches!(&calls[..], [Call::Reply { chat_id: GROUP, text }] if text.starts_with("Usage: /grant")),
+            "{calls:?}"
+        );
+        let roles = FakeMessage::group(GROUP, USER, "/roles");
+        assert_eq!(
+            handle(&app, Event::Message(roles)).await,
+            [reply(GROUP, "Roles:\n10 - owner\n20 - admin")]
+        );
+        let revoke = FakeMessage::group(GROUP, USER, "/revoke 10");
+        assert_eq!(
+            handle(&app, Event::Message(revoke)).await,
+            [reply(GROUP, "You can only manage roles below your own")]
+        );
+    }
+
+    #[tokio::test]
+    async fn help() {
+        let mut app = app();
+        app.add_help("help.header", "commands.help");
+        app.add_command("ping", |_: TestContext, _: FakeMessage| async { Ok(()) })
+            .description("commands.ping");
+        app.add_command("secret", |_: TestContext, _: FakeMessage| async { Ok(()) })
+            .description("commands.secret")
+            .role(Role::Admin);
+        app.publish_commands().await;
+        assert!(
+            app.client
+                .take_calls()
+                .iter()
+                .all(|call| matches!(call, Call::SetBotCommands(_)))
+        );
+
+        let help = FakeMessage::group(GROUP, USER, "/help");
+        assert_eq!(
+            handle(&app, Event::Message(help)).await,
+            [reply(
+                GROUP,
+                "help.header\n\nGeneral\n/help - commands.help\n/ping - commands.ping"
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn peer_menus() {
+        let mut app = app();
+        app.add_command("ping", |_: TestContext, _: FakeMessage| async { Ok(()) });
+
+        let peer = PackedChat {
+            ty: PackedType::User,
+            id: OWNER,
+            access_hash: None,
+        };
+        let request =
+            command::set_peer_commands(peer.to_input_peer(), Role::Owner, &app.commands(), |key| {
+                key.to_owned()
+            });
+        let hi = FakeMessage::private(OWNER, "hi");
+        assert_eq!(
+            handle(&app, Event::Message(hi)).await,
+            [Call::SetBotCommands(request)]
+        );
+        let again = FakeMessage::private(OWNER, "hi again");
+        assert_eq!(handle(&app, Event::Message(again)).await, []);
+        let user = FakeMessage::private(USER, "hi");
+        assert_eq!(handle(&app, Event::Message(user)).await, []);
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        let mut app = app();
+        app.set_error_reporter(ErrorReporter::new().reply("errors.failed").admin_chat(
+            PackedChat {
+                ty: PackedType::Chat,
+                id: ADMIN_CHAT,
+                access_hash: None,
+            },
+        ));
+        app.add_command("fail", |_: TestContext, _: FakeMessage| async {
+            anyhow::bail!("broken")
+        });
+        app.add_callback("fail", |_: TestContext, _: FakeCallbackQuery| async {
+            anyhow::bail!("broken")
+        });
+
+        let fail = FakeMessage::group(GROUP, USER, "/fail");
+        let calls = handle(&app, Event::Message(fail)).await;
+        assert!(
+            matches!(
+                &calls[..],
+                [Call::Reply { chat_id: GROUP, text }, Call::SendMessage { chat_id: ADMIN_CHAT, text: report }]
+                    if text == "errors.failed" && report.starts_with("message handler failed from 20")
+            ),
+            "{calls:?}"
+        );
+
+        let query = FakeCallbackQuery {
+            query_id: 5,
+            sender_id: USER,
+            data: callback::encode("fail", &()).unwrap(),
+        };
+        let calls = handle(&app, Event::CallbackQuery(query)).await;
+        assert_eq!(
+            calls[0],
+            Call::AnswerCallbackQuery {
+                query_id: 5,
+                message: Some("errors.failed".to_owned()),
+                alert: false,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn inline_commands() {
+        let mut app = app();
+        app.set_error_reporter(ErrorReporter::new().reply("errors.failed"));
+        app.add_inline_command(
+            "admin",
+            |context: TestContext, query: FakeInlineQuery, args: Vec<String>| async move {
+                inline_query::InlineQuery::new(query, context.client)
+                    .answer(args.into_iter().map(InlineArticle::new))
+                    .send()
+                    .await?;
+                Ok(())
+            },
+            |_: TestContext, _: FakeInlineSend, _: Vec<String>| async { anyhow::bail!("broken") },
+        )
+        .role(Role::Admin);
+
+        let query = FakeInlineQuery {
+            query_id: 7,
+            sender_id: USER,
+            text: "admin stats".to_owned(),
+        };
+        assert_eq!(
+            handle(&app, Event::InlineQuery(query)).await,
+            [Call::Answer {
+                query_id: 7,
+                titles: vec!["You are not allowed to do this".to_owned()],
+            }]
+        );
+
+        let query = FakeInlineQuery {
+            query_id: 8,
+            sender_id: OWNER,
+            text: "admin stats".to_owned(),
+        };
+        assert_eq!(
+            handle(&app, Event::InlineQuery(query)).await,
+            [Call::Answer {
+                query_id: 8,
+                titles: vec!["stats".to_owned()],
+            }]
+        );
+
+        let id = InputBotInlineMessageId::Id(
+            grammers_client::grammers_tl_types::types::InputBotInlineMessageId {
+                dc_id: 2,
+                id: 3,
+                access_hash: 4,
+            },
+        );
+        let send = FakeInlineSend {
+            sender_id: OWNER,
+            result_id: inline_command::result_id("admin", ["stats"]),
+            message_id: Some(id.clone()),
+        };
+        assert_eq!(
+            handle(&app, Event::InlineSend(send)).await,
+            [Call::EditInlineMessage {
+                id,
+                text: Some("errors.failed".to_owned()),
+            }]
+        );
+    }
 }
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use grammers_client::grammers_tl_types::enums;
+
+/// ## MemberChange
+/// What happened to the chat member
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemberChange {
+    Joined,
+    Left,
+}
+
+/// ## MemberUpdate
+/// User joined or left a chat, `chat_id` is the same id `Chat::id` returns
+#[derive(Clone, Debug)]
+pub struct MemberUpdate {
+    pub chat_id: i64,
+    pub user_id: i64,
+    /// User who added or removed the member, same as `user_id` if they joined or left on their own
+    pub actor_id: i64,
+    pub change: MemberChange,
+}
+
+impl MemberUpdate {
+    /// Build from a raw participant update, `None` for other updates and changes that are not joins or leaves
+    pub(crate) fn from_raw(update: &enums::Update) -> Option<Self> {
+        let (chat_id, user_id, actor_id, was_member, is_member) = match update {
+            enums::Update::ChatParticipant(u) => (
+                u.chat_id,
+                u.user_id,
+                u.actor_id,
+                u.prev_participant.is_some(),
+                u.new_participant.is_some(),
+            ),
+            enums::Update::ChannelParticipant(u) => (
+                u.channel_id,
+                u.user_id,
+                u.actor_id,
+                is_channel_member(&u.prev_participant),
+                is_channel_member(&u.new_participant),
+            ),
+            _ => return None,
+        };
+
+        let change = match (was_member, is_member) {
+            (false, true) => MemberChange::Joined,
+            (true, false) => MemberChange::Left,
+            _ => return None,
+        };
+
+        Some(Self {
+            chat_id,
+            user_id,
+            actor_id,
+            change,
+        })
+    }
+}
+
+fn is_channel_member(participant: &Option<enums::ChannelParticipant>) -> bool {
+    match participant {
+        Some(enums::ChannelParticipant::Banned(banned)) => !banned.left,
+        Some(enums::ChannelParticipant::Left(_)) | None => false,
+        Some(_) => true,
+    }
+}
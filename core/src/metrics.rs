@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use dashmap::DashMap;
+use std::{collections::BTreeMap, fmt::Write, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_util::sync::CancellationToken;
+
+/// Upper bounds of histogram buckets in seconds
+const BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0, 30.0, 60.0,
+];
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Series {
+    name: String,
+    labels: String,
+}
+
+impl Series {
+    fn new(name: &str, labels: &[(&str, &str)]) -> Self {
+        let labels = labels
+            .iter()
+            .map(|(name, value)| format!("{name}=\"{}\"", escape(value)))
+            .collect::<Vec<_>>()
+            .join(",");
+        Self {
+            name: name.to_owned(),
+            labels,
+        }
+    }
+
+    /// Sample line prefix, `extra` is appended to the labels
+    fn sample(&self, suffix: &str, extra: &str) -> String {
+        let labels = match (self.labels.is_empty(), extra.is_empty()) {
+            (true, true) => String::new(),
+            (false, true) => format!("{{{}}}", self.labels),
+            (true, false) => format!("{{{extra}}}"),
+            (false, false) => format!("{{{},{extra}}}", self.labels),
+        };
+        format!("{}{suffix}{labels}", self.name)
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[derive(Clone, Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+struct Registry {
+    counters: DashMap<Series, f64>,
+    gauges: DashMap<Series, f64>,
+    histograms: DashMap<Series, Histogram>,
+}
+
+/// ## Metrics
+/// Prometheus metrics of the bot, the core records updates, handlers and jobs, modules can add their own
+#[derive(Clone, Default)]
+pub struct Metrics {
+    registry: Arc<Registry>,
+}
+
+impl Metrics {
+    /// Increment the counter by one
+    pub fn inc(&self, name: &str, labels: &[(&str, &str)]) {
+        self.add(name, labels, 1.0);
+    }
+
+    /// Increment the counter by `value`
+    pub fn add(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        *self
+            .registry
+            .counters
+            .entry(Series::new(name, labels))
+            .or_default() += value;
+    }
+
+    /// Set the gauge to `value`
+    pub fn set(&self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.registry
+            .gauges
+            .insert(Series::new(name, labels), value);
+    }
+
+    /// Record duration in the histogram, buckets go from 5 milliseconds to a minute
+    pub fn observe(&self, name: &str, labels: &[(&str, &str)], duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let mut histogram = self
+            .registry
+            .histograms
+            .entry(Series::new(name, labels))
+            .or_default();
+        for (bucket, bound) in histogram.buckets.iter_mut().zip(BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+
+    /// All metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+
+        for (kind, series) in [
+            ("counter", &self.registry.counters),
+            ("gauge", &self.registry.gauges),
+        ] {
+            let sorted: BTreeMap<_, _> = series.iter().map(|s| (s.key().clone(), *s)).collect();
+            let mut last = None;
+            for (series, value) in &sorted {
+                if last != Some(&series.name) {
+                    let _ = writeln!(text, "# TYPE {} {kind}", series.name);
+                    last = Some(&series.name);
+                }
+                let _ = writeln!(text, "{} {value}", series.sample("", ""));
+            }
+        }
+
+        let histograms: BTreeMap<_, _> = self
+            .registry
+            .histograms
+            .iter()
+            .map(|h| (h.key().clone(), h.value().clone()))
+            .collect();
+        let mut last = None;
+        for (series, histogram) in &histograms {
+            if last != Some(&series.name) {
+                let _ = writeln!(text, "# TYPE {} histogram", series.name);
+                last = Some(&series.name);
+            }
+            for (count, bound) in histogram.buckets.iter().zip(BUCKETS) {
+                let le = format!("le=\"{bound}\"");
+                let _ = writeln!(text, "{} {count}", series.sample("_bucket", &le));
+            }
+            let _ = writeln!(
+                text,
+                "{} {}",
+                series.sample("_bucket", "le=\"+Inf\""),
+                histogram.count
+            );
+            let _ = writeln!(text, "{} {}", series.sample("_sum", ""), histogram.sum);
+            let _ = writeln!(text, "{} {}", series.sample("_count", ""), histogram.count);
+        }
+
+        text
+    }
+}
+
+/// Serve `/metrics` over HTTP on `address` until the token is cancelled
+pub(crate) async fn serve(metrics: Metrics, address: SocketAddr, token: CancellationToken) {
+    let listener = match TcpListener::bind(address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            tracing::error!(%address, error = %e, "failed to start metrics server");
+            return;
+        }
+    };
+    tracing::info!(%address, "serving metrics");
+
+    loop {
+        let stream = tokio::select! {
+            _ = token.cancelled() => return,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to accept metrics connection");
+                    continue;
+                }
+            },
+        };
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            let response = tokio::time::timeout(Duration::from_secs(10), respond(stream, &metrics));
+            if let Ok(Err(e)) = response.await {
+                tracing::debug!(error = %e, "failed to serve metrics");
+            }
+        });
+    }
+}
+
+async fn respond(mut stream: TcpStream, metrics: &Metrics) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let path = request
+        .split(|&b| b == b' ')
+        .nth(1)
+        .and_then(|path| std::str::from_utf8(path).ok());
+    let (status, body) = match path {
+        Some("/metrics") => ("200 OK", metrics.render()),
+        _ => ("404 Not Found", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_and_gauges() {
+        let metrics = Metrics::default();
+        metrics.inc("updates_total", &[("kind", "message")]);
+        metrics.add("updates_total", &[("kind", "message")], 2.0);
+        metrics.inc("updates_total", &[("kind", "a \"quoted\"\\\nline")]);
+        metrics.set("connected", &[], 1.0);
+        assert_eq!(
+            metrics.render(),
+            "# TYPE updates_total counter\n\
+             updates_total{kind=\"a \\\"quoted\\\"\\\\\\nline\"} 1\n\
+             updates_total{kind=\"message\"} 3\n\
+             # TYPE connected gauge\n\
+             connected 1\n"
+        );
+    }
+
+    #[test]
+    fn histograms() {
+        let metrics = Metrics::default();
+        let labels = [("job", "track_all")];
+        metrics.observe("job_seconds", &labels, Duration::from_millis(5));
+        metrics.observe("job_seconds", &labels, Duration::from_millis(300));
+        metrics.observe("job_seconds", &labels, Duration::from_secs(120));
+
+        let text = metrics.render();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines[0], "# TYPE job_seconds histogram");
+        assert_eq!(
+            lines[1],
+            "job_seconds_bucket{job=\"track_all\",le=\"0.005\"} 1"
+        );
+        assert_eq!(
+            lines[6],
+            "job_seconds_bucket{job=\"track_all\",le=\"0.25\"} 1"
+        );
+        assert_eq!(
+            lines[7],
+            "job_seconds_bucket{job=\"track_all\",le=\"0.5\"} 2"
+        );
+        assert_eq!(
+            lines[12],
+            "job_seconds_bucket{job=\"track_all\",le=\"60\"} 2"
+        );
+        assert_eq!(
+            lines[13],
+            "job_seconds_bucket{job=\"track_all\",le=\"+Inf\"} 3"
+        );
+        assert_eq!(lines[14], "job_seconds_sum{job=\"track_all\"} 120.305");
+        assert_eq!(lines[15], "job_seconds_count{job=\"track_all\"} 3");
+        assert_eq!(lines.len(), 16);
+    }
+}
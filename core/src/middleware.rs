@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{
+    client::{BotClient, IncomingUpdate},
+    member::MemberUpdate,
+};
+use grammers_client::{Client, grammers_tl_types::enums, types::MessageDeletion};
+
+/// ## Event
+/// Update that is passed through the middleware chain
+#[derive(Clone)]
+pub enum Event<C: BotClient = Client> {
+    Message(C::Message),
+    CallbackQuery(C::CallbackQuery),
+    InlineQuery(C::InlineQuery),
+    InlineSend(C::InlineSend),
+    MessageEdited(C::Message),
+    MessageDeleted(MessageDeletion),
+    /// Someone other than the bot joined or left a chat
+    MemberUpdate(MemberUpdate),
+    /// The bot was added to or removed from a chat
+    BotMembership(MemberUpdate),
+    /// Update that has no dedicated event
+    Raw(enums::Update),
+}
+
+impl<C: BotClient> Event<C> {
+    /// Short name of the update kind, useful for logging and metrics
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Event::Message(_) => "message",
+            Event::CallbackQuery(_) => "callback_query",
+            Event::InlineQuery(_) => "inline_query",
+            Event::InlineSend(_) => "inline_send",
+            Event::MessageEdited(_) => "message_edited",
+            Event::MessageDeleted(_) => "message_deleted",
+            Event::MemberUpdate(_) => "member_update",
+            Event::BotMembership(_) => "bot_membership",
+            Event::Raw(_) => "raw",
+        }
+    }
+
+    /// Id of the user or chat that caused this update
+    pub fn sender_id(&self) -> Option<i64> {
+        match self {
+            Event::Message(message) | Event::MessageEdited(message) => message.sender_id(),
+            Event::CallbackQuery(query) => query.sender_id(),
+            Event::InlineQuery(query) => query.sender_id(),
+            Event::InlineSend(send) => send.sender_id(),
+            Event::MemberUpdate(update) | Event::BotMembership(update) => Some(update.actor_id),
+            Event::MessageDeleted(_) | Event::Raw(_) => None,
+        }
+    }
+
+    /// Id of the chat where this update happened, inline queries have no chat
+    pub fn chat_id(&self) -> Option<i64> {
+        match self {
+            Event::Message(message) | Event::MessageEdited(message) => message.chat_id(),
+            Event::CallbackQuery(query) => query.chat_id(),
+            Event::MessageDeleted(deletion) => deletion.channel_id(),
+            Event::MemberUpdate(update) | Event::BotMembership(update) => Some(update.chat_id),
+            Event::InlineQuery(_) | Event::InlineSend(_) | Event::Raw(_) => None,
+        }
+    }
+
+    /// Language code of the Telegram app of the user who caused this update, if Telegram sent it
+    pub fn lang_code(&self) -> Option<String> {
+        match self {
+            Event::Message(message) | Event::MessageEdited(message) => message.lang_code(),
+            Event::CallbackQuery(query) => query.lang_code(),
+            Event::InlineQuery(query) => query.lang_code(),
+            Event::InlineSend(send) => send.lang_code(),
+            _ => None,
+        }
+    }
+}
+
+/// ## Flow
+/// Decision made by a `before` middleware
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Pass the update to the next middleware and then to the handler
+    Continue,
+    /// Drop the update, no other middleware or handler will see it
+    Stop,
+}
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{client::BotClient, inline_message_ext::InlineEdit};
+use grammers_client::{
+    Client, InvocationError,
+    grammers_tl_types::enums::{self, InputBotInlineMessageId},
+    reply_markup::ReplyMarkup,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+    time::Instant,
+};
+use tokio_util::sync::CancellationToken;
+
+const BAR_WIDTH: usize = 10;
+
+/// Render `done` out of `total` as a bar with percentage, e.g. `[█████░░░░░] 50%`
+pub fn bar(done: u64, total: u64) -> String {
+    let ratio = if total == 0 {
+        0.0
+    } else {
+        (done as f64 / total as f64).clamp(0.0, 1.0)
+    };
+    let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+    format!(
+        "[{}{}] {}%",
+        "█".repeat(filled),
+        "░".repeat(BAR_WIDTH - filled),
+        (ratio * 100.0).round() as u64
+    )
+}
+
+/// ## ProgressReporter
+/// Shows progress in an inline message, updates are coalesced and edits are throttled so Telegram does not flood wait the bot
+pub struct ProgressReporter<C: BotClient = Client> {
+    client: C,
+    message_id: InputBotInlineMessageId,
+    min_interval: Duration,
+    reply_markup: Option<enums::ReplyMarkup>,
+}
+
+impl<C: BotClient> ProgressReporter<C> {
+    pub const fn new(client: C, message_id: InputBotInlineMessageId) -> Self {
+        Self {
+            client,
+            message_id,
+            min_interval: Duration::from_secs(3),
+            reply_markup: None,
+        }
+    }
+
+    /// Minimum time between edits, 3 seconds by default
+    pub const fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Keyboard shown with every progress edit
+    pub fn reply_markup<RM: ReplyMarkup>(mut self, markup: &RM) -> Self {
+        self.reply_markup = Some(markup.to_reply_markup().raw);
+        self
+    }
+
+    /// Start editing the message in the background
+    pub fn start(self) -> Progress {
+        let (sender, receiver) = watch::channel(String::new());
+        let token = CancellationToken::new();
+        let task = tokio::spawn(self.run(receiver, token.clone()));
+        Progress {
+            sender: Arc::new(sender),
+            token,
+            task,
+        }
+    }
+
+    async fn run(self, mut receiver: watch::Receiver<String>, token: CancellationToken) {
+        let mut shown = String::new();
+        let mut next_edit = Instant::now();
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => return,
+                changed = receiver.changed() => if changed.is_err() {
+                    return;
+                },
+            }
+            tokio::select! {
+                _ = token.cancelled() => return,
+                _ = tokio::time::sleep_until(next_edit) => {}
+            }
+
+            let text = receiver.borrow_and_update().clone();
+            if text == shown {
+                continue;
+            }
+
+            let edit = InlineEdit::text(text.clone()).raw_reply_markup(self.reply_markup.clone());
+            match self
+                .client
+                .edit_inline_message(self.message_id.clone(), edit)
+                .await
+            {
+                Ok(_) => shown = text,
+                Err(InvocationError::Rpc(rpc)) if rpc.name == "MESSAGE_NOT_MODIFIED" => {
+                    shown = text
+                }
+                Err(InvocationError::Rpc(rpc)) if rpc.name == "FLOOD_WAIT" => {
+                    next_edit = Instant::now()
+                        + Duration::from_secs(rpc.value.unwrap_or(0).into())
+                        + self.min_interval;
+                    receiver.mark_changed();
+                    continue;
+                }
+                Err(e) => tracing::warn!(error = %e, "failed to show progress"),
+            }
+            next_edit = Instant::now() + self.min_interval;
+        }
+    }
+}
+
+/// ## Progress
+/// Handle to a running `ProgressReporter`, only the latest text is shown
+pub struct Progress {
+    sender: Arc<watch::Sender<String>>,
+    token: CancellationToken,
+    task: JoinHandle<()>,
+}
+
+impl Progress {
+    pub fn set(&self, text: impl Into<String>) {
+        self.sender.send_replace(text.into());
+    }
+
+    /// Show `text` with a percentage bar of `done` out of `total` bytes
+    pub fn set_bytes(&self, text: &str, done: u64, total: u64) {
+        self.set(format!("{text}\n{}", bar(done, total)));
+    }
+
+    /// Channel for code that reports progress as strings, it is closed when the reporter is stopped
+    pub fn sender(&self) -> mpsc::Sender<String> {
+        let (sender, mut receiver) = mpsc::channel(16);
+        let progress = self.sender.clone();
+        let token = self.token.clone();
+        tokio::spawn(async move {
+            loop {
+                let text = tokio::select! {
+                    _ = token.cancelled() => return,
+                    text = receiver.recv() => text,
+                };
+                let Some(text) = text else {
+                    return;
+                };
+                progress.send_replace(text);
+            }
+        });
+        sender
+    }
+
+    /// Stop editing the message and wait for the edit in flight, so it cannot overwrite edits made after this
+    pub async fn stop(self) {
+        self.token.cancel();
+        let _ = self.task.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars() {
+        assert_eq!(bar(0, 0), "[░░░░░░░░░░] 0%");
+        assert_eq!(bar(5, 0), "[░░░░░░░░░░] 0%");
+        assert_eq!(bar(0, 10), "[░░░░░░░░░░] 0%");
+        assert_eq!(bar(1, 3), "[███░░░░░░░] 33%");
+        assert_eq!(bar(1, 2), "[█████░░░░░] 50%");
+        assert_eq!(bar(10, 10), "[██████████] 100%");
+        assert_eq!(bar(15, 10), "[██████████] 100%");
+        assert_eq!(bar(u64::MAX, 1), "[██████████] 100%");
+    }
+}
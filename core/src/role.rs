@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{
+    Context,
+    args::{ArgSpec, Args, ArgsError, FromArgs},
+    client::BotClient,
+    user_store::{MemoryUserStore, UserStore},
+};
+use dashmap::DashMap;
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+/// ## Role
+/// What a user is allowed to do, roles are ordered from `Banned` to `Owner`
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Role {
+    /// Updates caused by the user are dropped before any middleware, except membership changes
+    Banned,
+    User,
+    Admin,
+    Owner,
+}
+
+impl Role {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Role::Banned => "banned",
+            Role::User => "user",
+            Role::Admin => "admin",
+            Role::Owner => "owner",
+        }
+    }
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "banned" => Ok(Role::Banned),
+            "user" => Ok(Role::User),
+            "admin" => Ok(Role::Admin),
+            "owner" => Ok(Role::Owner),
+            _ => anyhow::bail!("unknown role {s}"),
+        }
+    }
+}
+
+/// ## Roles
+/// Roles of all users, roles set with `MystbotCore::set_role` come from the config and can not be changed with commands
+#[derive(Clone)]
+pub struct Roles {
+    pub(crate) store: Arc<dyn UserStore<Role>>,
+    fixed: Arc<DashMap<i64, Role>>,
+}
+
+impl Default for Roles {
+    fn default() -> Self {
+        Self {
+            store: Arc::new(MemoryUserStore::new()),
+            fixed: Arc::new(DashMap::new()),
+        }
+    }
+}
+
+impl Roles {
+    pub(crate) fn set_fixed(&self, user_id: i64, role: Role) {
+        self.fixed.insert(user_id, role);
+    }
+
+    /// Role of the user, `Role::User` if they have none
+    pub async fn role(&self, user_id: i64) -> Role {
+        if let Some(role) = self.fixed.get(&user_id) {
+            return *role;
+        }
+        match self.store.load(user_id).await {
+            Ok(role) => role.unwrap_or(Role::User),
+            Err(e) => {
+                tracing::warn!(user_id, error = ?e, "failed to load role");
+                Role::User
+            }
+        }
+    }
+
+    /// Give the user `role`, returns `false` if their role comes from the config
+    pub async fn grant(&self, user_id: i64, role: Role) -> anyhow::Result<bool> {
+        if self.fixed.contains_key(&user_id) {
+            return Ok(false);
+        }
+        self.store.save(user_id, role).await?;
+        Ok(true)
+    }
+
+    /// Make the user a `Role::User` again, returns `false` if their role comes from the config
+    pub async fn revoke(&self, user_id: i64) -> anyhow::Result<bool> {
+        if self.fixed.contains_key(&user_id) {
+            return Ok(false);
+        }
+        self.store.remove(user_id).await?;
+        Ok(true)
+    }
+
+    /// Users with a role other than `Role::User`, from the highest role
+    pub async fn list(&self) -> anyhow::Result<Vec<(i64, Role)>> {
+        let mut roles = self.store.list().await?;
+        roles.retain(|(user_id, _)| !self.fixed.contains_key(user_id));
+        roles.extend(self.fixed.iter().map(|r| (*r.key(), *r.value())));
+        roles.retain(|(_, role)| *role != Role::User);
+        roles.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Ok(roles)
+    }
+}
+
+pub(crate) struct GrantArgs {
+    user_id: i64,
+    role: Role,
+}
+
+impl FromArgs for GrantArgs {
+    fn spec() -> ArgSpec {
+        ArgSpec::new()
+            .required("user_id", "args.user_id")
+            .required("role", "args.role")
+    }
+
+    fn from_args(mut args: Args) -> Result<Self, ArgsError> {
+        Ok(Self {
+            user_id: args.take("user_id")?,
+            role: args.take("role")?,
+        })
+    }
+}
+
+pub(crate) struct RevokeArgs {
+    user_id: i64,
+}
+
+impl FromArgs for RevokeArgs {
+    fn spec() -> ArgSpec {
+        ArgSpec::new().required("user_id", "args.user_id")
+    }
+
+    fn from_args(mut args: Args) -> Result<Self, ArgsError> {
+        Ok(Self {
+            user_id: args.take("user_id")?,
+        })
+    }
+}
+
+/// Built-in `/grant`, users can only manage roles below their own
+pub(crate) async fn grant<State, C: BotClient>(
+    context: Context<State, C>,
+    message: C::Message,
+    args: GrantArgs,
+) -> anyhow::Result<()> {
+    let target = context.roles().role(args.user_id).await;
+    let text = if args.role >= context.role() || target >= context.role() {
+        context.text("roles.too_high", &[])
+    } else if !context.roles().grant(args.user_id, args.role).await? {
+        context.text("roles.fixed", &[("user_id", args.user_id.into())])
+    } else {
+        context.text(
+            "roles.granted",
+            &[
+                ("user_id", args.user_id.into()),
+                ("role", args.role.as_str().into()),
+            ],
+        )
+    };
+    context.client.reply(&message, text).await?;
+    Ok(())
+}
+
+/// Built-in `/revoke`, users can only manage roles below their own
+pub(crate) async fn revoke<State, C: BotClient>(
+    context: Context<State, C>,
+    message: C::Message,
+    args: RevokeArgs,
+) -> anyhow::Result<()> {
+    let target = context.roles().role(args.user_id).await;
+    let text = if target >= context.role() {
+        context.text("roles.too_high", &[])
+    } else if !context.roles().revoke(args.user_id).await? {
+        context.text("roles.fixed", &[("user_id", args.user_id.into())])
+    } else {
+        context.text("roles.revoked", &[("user_id", args.user_id.into())])
+    };
+    context.client.reply(&message, text).await?;
+    Ok(())
+}
+
+/// Built-in `/roles`
+pub(crate) async fn list<State, C: BotClient>(
+    context: Context<State, C>,
+    message: C::Message,
+) -> anyhow::Result<()> {
+    let roles = context.roles().list().await?;
+    let text = if roles.is_empty() {
+        context.text("roles.empty", &[])
+    } else {
+        let roles = roles
+            .into_iter()
+            .map(|(user_id, role)| format!("{user_id} - {role}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        context.text("roles.list", &[("roles", roles.into())])
+    };
+    context.client.reply(&message, text).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        for role in [Role::Banned, Role::User, Role::Admin, Role::Owner] {
+            assert_eq!(role.to_string().parse::<Role>().unwrap(), role);
+        }
+        assert_eq!("Admin".parse::<Role>().unwrap(), Role::Admin);
+        assert_eq!("OWNER".parse::<Role>().unwrap(), Role::Owner);
+        assert!("moderator".parse::<Role>().is_err());
+        assert!("".parse::<Role>().is_err());
+    }
+
+    #[test]
+    fn order() {
+        assert!(Role::Banned < Role::User);
+        assert!(Role::User < Role::Admin);
+        assert!(Role::Admin < Role::Owner);
+        assert_eq!(
+            [Role::Admin, Role::Owner, Role::Banned, Role::User]
+                .into_iter()
+                .max(),
+            Some(Role::Owner)
+        );
+    }
+
+    #[tokio::test]
+    async fn roles() {
+        let roles = Roles::default();
+        roles.set_fixed(1, Role::Owner);
+        assert_eq!(roles.role(1).await, Role::Owner);
+        assert_eq!(roles.role(2).await, Role::User);
+
+        assert!(roles.grant(2, Role::Admin).await.unwrap());
+        assert!(roles.grant(3, Role::Banned).await.unwrap());
+        assert!(!roles.grant(1, Role::User).await.unwrap());
+        assert_eq!(roles.role(2).await, Role::Admin);
+        assert_eq!(
+            roles.list().await.unwrap(),
+            [(1, Role::Owner), (2, Role::Admin), (3, Role::Banned)]
+        );
+
+        assert!(roles.revoke(2).await.unwrap());
+        assert!(!roles.revoke(1).await.unwrap());
+        assert_eq!(roles.role(2).await, Role::User);
+        assert_eq!(roles.role(1).await, Role::Owner);
+    }
+}
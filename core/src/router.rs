@@ -0,0 +1,412 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{
+    args::ArgSpec,
+    callback,
+    client::BotClient,
+    command::{self, CommandData, CommandInfo, CommandOptions, CommandScope},
+    handler::BoxedHandler,
+    inline_command::{self, InlineCommandData, InlineCommandOptions},
+    role::Role,
+};
+use dashmap::DashMap;
+use grammers_client::Client;
+
+/// ## Incoming
+/// Parts of an update that decide which handler gets it, built from real updates by `MystbotCore` and by hand in tests
+#[derive(Clone, Copy)]
+pub(crate) enum Incoming<'a> {
+    Message(&'a str),
+    MessageEdited(&'a str),
+    CallbackQuery(&'a [u8]),
+    InlineQuery(&'a str),
+    InlineSend(&'a str),
+}
+
+/// ## Route
+/// Handler picked for an update, `handler` is its name for logs and metrics
+pub(crate) enum Route<State, C: BotClient> {
+    Command {
+        handler: String,
+        func: BoxedHandler<State, (C::Message,), C>,
+    },
+    Callback {
+        handler: String,
+        func: BoxedHandler<State, (C::CallbackQuery,), C>,
+    },
+    InlineQuery {
+        handler: String,
+        func: BoxedHandler<State, (C::InlineQuery, Vec<String>), C>,
+        args: Vec<String>,
+    },
+    InlineFallback {
+        func: BoxedHandler<State, (C::InlineQuery,), C>,
+    },
+    InlineSend {
+        handler: String,
+        func: BoxedHandler<State, (C::InlineSend, Vec<String>), C>,
+        args: Vec<String>,
+    },
+    /// The user's role is too low for the command or inline command, they are told so
+    Denied { handler: String },
+    /// Edit of a message that is not a command handling edits, goes to the edited message handlers
+    Edited,
+    /// Not a command or a button with a handler, it may be a reply in a conversation
+    Conversation,
+    /// Nothing handles the update
+    Ignore,
+}
+
+/// ## Router
+/// Registered commands, buttons and inline commands, picks the handler of an update without talking to Telegram
+pub(crate) struct Router<State, C: BotClient = Client> {
+    username: Option<String>,
+    command_order: Vec<String>,
+    commands: DashMap<String, CommandData<State, C>>,
+    aliases: DashMap<String, String>,
+    callbacks: DashMap<String, BoxedHandler<State, (C::CallbackQuery,), C>>,
+    inline_commands: DashMap<String, InlineCommandData<State, C>>,
+    inline_fallback: Option<BoxedHandler<State, (C::InlineQuery,), C>>,
+}
+
+impl<State, C: BotClient> Router<State, C> {
+    /// Commands addressed to other bots than `username` are ignored
+    pub(crate) fn new(username: Option<String>) -> Self {
+        Self {
+            username,
+            command_order: vec![],
+            commands: DashMap::new(),
+            aliases: DashMap::new(),
+            callbacks: DashMap::new(),
+            inline_commands: DashMap::new(),
+            inline_fallback: None,
+        }
+    }
+
+    pub(crate) fn add_command(
+        &mut self,
+        command: String,
+        args: ArgSpec,
+        module: String,
+        func: BoxedHandler<State, (C::Message,), C>,
+    ) -> CommandOptions<'_, State, C> {
+        let command = command.to_lowercase();
+        if !self.command_order.contains(&command) {
+            self.command_order.push(command.clone());
+        }
+        self.commands.insert(
+            command.clone(),
+            CommandData {
+                func,
+                handle_edits: false,
+                info: CommandInfo {
+                    name: command.clone(),
+                    usage: None,
+                    args,
+                    description: None,
+                    module,
+                    scope: CommandScope::All,
+                    role: Role::User,
+                },
+            },
+        );
+        CommandOptions {
+            data: self.commands.get_mut(&command).unwrap(),
+            aliases: &self.aliases,
+        }
+    }
+
+    /// Registered commands in registration order
+    pub(crate) fn commands(&self) -> Vec<CommandInfo> {
+        self.command_order
+            .iter()
+            .filter_map(|c| Some(self.commands.get(c)?.info.clone()))
+            .collect()
+    }
+
+    pub(crate) fn add_callback(
+        &mut self,
+        prefix: String,
+        func: BoxedHandler<State, (C::CallbackQuery,), C>,
+    ) {
+        self.callbacks.insert(prefix, func);
+    }
+
+    pub(crate) fn add_inline_command(
+        &mut self,
+        command: String,
+        query: BoxedHandler<State, (C::InlineQuery, Vec<String>), C>,
+        send: BoxedHandler<State, (C::InlineSend, Vec<String>), C>,
+    ) -> InlineCommandOptions<'_, State, C> {
+        self.inline_commands.insert(
+            command.clone(),
+            InlineCommandData {
+                query,
+                send,
+                role: Role::User,
+            },
+        );
+        InlineCommandOptions {
+            data: self.inline_commands.get_mut(&command).unwrap(),
+        }
+    }
+
+    pub(crate) fn set_inline_fallback(&mut self, func: BoxedHandler<State, (C::InlineQuery,), C>) {
+        self.inline_fallback = Some(func);
+    }
+
+    /// Pick the handler of the update sent by a user with `role`
+    pub(crate) fn route(&self, incoming: Incoming, role: Role) -> Route<State, C> {
+        match incoming {
+            Incoming::Message(text) => {
+                let Some((name, username)) = command::parse(text) else {
+                    return Route::Conversation;
+                };
+                let Some(command) = self.find_command(name, username) else {
+                    return Route::Ignore;
+                };
+                let handler = format!("/{}", name.to_lowercase());
+                if role < command.info.role {
+                    return Route::Denied { handler };
+                }
+                Route::Command {
+                    handler,
+                    func: command.func.clone(),
+                }
+            }
+            Incoming::MessageEdited(text) => {
+                let command = command::parse(text)
+                    .and_then(|(name, username)| self.find_command(name, username))
+                    .filter(|command| command.handle_edits);
+                match command {
+                    Some(command) if role < command.info.role => Route::Ignore,
+                    Some(command) => Route::Command {
+                        handler: "edited_command".to_owned(),
+                        func: command.func.clone(),
+                    },
+                    None => Route::Edited,
+                }
+            }
+            Incoming::CallbackQuery(data) => {
+                let callback = callback::split(data)
+                    .and_then(|(prefix, _)| Some((prefix, self.callbacks.get(prefix)?.clone())));
+                match callback {
+                    Some((prefix, func)) => Route::Callback {
+                        handler: format!("callback:{prefix}"),
+                        func,
+                    },
+                    None => Route::Conversation,
+                }
+            }
+            Incoming::InlineQuery(text) => {
+                let command = inline_command::parse_query(text)
+                    .and_then(|(name, args)| Some((name, self.inline_commands.get(name)?, args)));
+                match (command, &self.inline_fallback) {
+                    (Some((name, command, _)), _) if role < command.role => Route::Denied {
+                        handler: format!("inline:{name}"),
+                    },
+                    (Some((name, command, args)), _) => Route::InlineQuery {
+                        handler: format!("inline:{name}"),
+                        func: command.query.clone(),
+                        args,
+                    },
+                    (None, Some(func)) => Route::InlineFallback { func: func.clone() },
+                    (None, None) => Route::Ignore,
+                }
+            }
+            Incoming::InlineSend(result_id) => {
+                let (name, args) = inline_command::parse_result_id(result_id);
+                match self.inline_commands.get(name) {
+                    Some(command) if role >= command.role => Route::InlineSend {
+                        handler: format!("inline:{name}"),
+                        func: command.send.clone(),
+                        args,
+                    },
+                    _ => Route::Ignore,
+                }
+            }
+        }
+    }
+
+    /// Look up command by name or alias, commands addressed to other bots are ignored
+    fn find_command(
+        &self,
+        name: &str,
+        username: Option<&str>,
+    ) -> Option<dashmap::mapref::one::Ref<'_, String, CommandData<State, C>>> {
+        let addressed_to_me = username.is_none_or(|username| {
+            self.username
+                .as_deref()
+                .is_some_and(|me| me.eq_ignore_ascii_case(username))
+        });
+        if !addressed_to_me {
+            return None;
+        }
+
+        let name = name.to_lowercase();
+        match self.commands.get(&name) {
+            Some(command) => Some(command),
+            None => self.commands.get(self.aliases.get(&name)?.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+    use grammers_client::types::{CallbackQuery, InlineQuery, InlineSend, Message};
+    use std::sync::Arc;
+
+    fn router() -> Router<()> {
+        let mut router = Router::new(Some("MystBot".to_owned()));
+        let command: BoxedHandler<(), (Message,)> =
+            Arc::new(|_: Context<()>, _: Message| async { anyhow::Ok(()) });
+        router
+            .add_command(
+                "Start".to_owned(),
+                ArgSpec::new(),
+                String::new(),
+                command.clone(),
+            )
+            .alias("Begin");
+        router
+            .add_command(
+                "edit".to_owned(),
+                ArgSpec::new(),
+                String::new(),
+                command.clone(),
+            )
+            .handle_edits();
+        router
+            .add_command("grant".to_owned(), ArgSpec::new(), String::new(), command)
+            .role(Role::Admin);
+        router.add_callback(
+            "music".to_owned(),
+            Arc::new(|_: Context<()>, _: CallbackQuery| async { anyhow::Ok(()) }),
+        );
+        router
+            .add_inline_command(
+                "music".to_owned(),
+                Arc::new(|_: Context<()>, _: InlineQuery, _: Vec<String>| async { anyhow::Ok(()) }),
+                Arc::new(|_: Context<()>, _: InlineSend, _: Vec<String>| async { anyhow::Ok(()) }),
+            )
+            .role(Role::Admin);
+        router
+    }
+
+    fn route(router: &Router<()>, incoming: Incoming, role: Role) -> String {
+        match router.route(incoming, role) {
+            Route::Command { handler, .. } => format!("command {handler}"),
+            Route::Callback { handler, .. } => format!("callback {handler}"),
+            Route::InlineQuery { handler, args, .. } => format!("inline {handler} {args:?}"),
+            Route::InlineFallback { .. } => "fallback".to_owned(),
+            Route::InlineSend { handler, args, .. } => format!("send {handler} {args:?}"),
+            Route::Denied { handler } => format!("denied {handler}"),
+            Route::Edited => "edited".to_owned(),
+            Route::Conversation => "conversation".to_owned(),
+            Route::Ignore => "ignore".to_owned(),
+        }
+    }
+
+    #[test]
+    fn commands() {
+        let router = router();
+        let message = |text| route(&router, Incoming::Message(text), Role::User);
+        assert_eq!(message("/start"), "command /start");
+        assert_eq!(message("/START now"), "command /start");
+        assert_eq!(message("/start@mystbot"), "command /start");
+        assert_eq!(message("/start@OtherBot"), "ignore");
+        assert_eq!(message("/begin"), "command /begin");
+        assert_eq!(message("/unknown"), "ignore");
+        assert_eq!(message("hello"), "conversation");
+    }
+
+    #[test]
+    fn command_roles() {
+        let router = router();
+        assert_eq!(
+            route(&router, Incoming::Message("/grant"), Role::User),
+            "denied /grant"
+        );
+        assert_eq!(
+            route(&router, Incoming::Message("/grant"), Role::Admin),
+            "command /grant"
+        );
+        assert_eq!(
+            route(&router, Incoming::MessageEdited("/grant"), Role::User),
+            "edited"
+        );
+    }
+
+    #[test]
+    fn edits() {
+        let router = router();
+        let edited = |text| route(&router, Incoming::MessageEdited(text), Role::User);
+        assert_eq!(edited("/edit"), "command edited_command");
+        assert_eq!(edited("/start"), "edited");
+        assert_eq!(edited("hello"), "edited");
+    }
+
+    #[test]
+    fn callbacks() {
+        let router = router();
+        let data = callback::encode("music", &()).unwrap();
+        assert_eq!(
+            route(&router, Incoming::CallbackQuery(&data), Role::User),
+            "callback callback:music"
+        );
+        let data = callback::encode("other", &()).unwrap();
+        assert_eq!(
+            route(&router, Incoming::CallbackQuery(&data), Role::User),
+            "conversation"
+        );
+        assert_eq!(
+            route(&router, Incoming::CallbackQuery(b"garbage"), Role::User),
+            "conversation"
+        );
+    }
+
+    #[test]
+    fn inline() {
+        let mut router = router();
+        assert_eq!(
+            route(
+                &router,
+                Incoming::InlineQuery("music never gonna"),
+                Role::Admin
+            ),
+            r#"inline inline:music ["never", "gonna"]"#
+        );
+        assert_eq!(
+            route(&router, Incoming::InlineQuery("music"), Role::User),
+            "denied inline:music"
+        );
+        assert_eq!(
+            route(&router, Incoming::InlineQuery("video"), Role::User),
+            "ignore"
+        );
+        router.set_inline_fallback(Arc::new(|_: Context<()>, _: InlineQuery| async {
+            anyhow::Ok(())
+        }));
+        assert_eq!(
+            route(&router, Incoming::InlineQuery("video"), Role::User),
+            "fallback"
+        );
+
+        let id = inline_command::result_id("music", ["42"]);
+        assert_eq!(
+            route(&router, Incoming::InlineSend(&id), Role::Admin),
+            r#"send inline:music ["42"]"#
+        );
+        assert_eq!(
+            route(&router, Incoming::InlineSend(&id), Role::User),
+            "ignore"
+        );
+        assert_eq!(
+            route(&router, Incoming::InlineSend("video|1"), Role::Admin),
+            "ignore"
+        );
+    }
+}
@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use crate::{client::BotClient, handler::BoxedHandler};
+use chrono::{DateTime, Utc};
+use dashmap::{DashMap, mapref::one::RefMut};
+use grammers_client::Client;
+use std::{
+    str::FromStr,
+    sync::{Arc, atomic::AtomicBool},
+    time::{Duration, SystemTime},
+};
+use tokio::sync::Notify;
+
+/// ## Schedule
+/// When a job runs
+#[derive(Clone)]
+pub enum Schedule {
+    Every(Duration),
+    Cron(Box<cron::Schedule>),
+}
+
+impl Schedule {
+    pub const fn every(interval: Duration) -> Self {
+        Schedule::Every(interval)
+    }
+
+    /// Cron expression with seconds, e.g. `0 */10 * * * *` for every 10 minutes, times are in UTC
+    pub fn cron(expression: &str) -> anyhow::Result<Self> {
+        Ok(Schedule::Cron(Box::new(cron::Schedule::from_str(
+            expression,
+        )?)))
+    }
+
+    /// First time after `time` the job should run, `None` if it never runs again
+    pub fn next_after(&self, time: SystemTime) -> Option<SystemTime> {
+        match self {
+            Schedule::Every(interval) => Some(time + *interval),
+            Schedule::Cron(schedule) => schedule
+                .after(&DateTime::<Utc>::from(time))
+                .next()
+                .map(SystemTime::from),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct JobData<State, C: BotClient> {
+    pub(crate) schedule: Schedule,
+    pub(crate) jitter: Duration,
+    pub(crate) allow_overlap: bool,
+    pub(crate) func: BoxedHandler<State, (), C>,
+    pub(crate) running: Arc<AtomicBool>,
+    pub(crate) trigger: Arc<Notify>,
+}
+
+impl<State, C: BotClient> JobData<State, C> {
+    /// Next scheduled run with a random delay of up to `jitter` added
+    pub(crate) fn next_run(&self, time: SystemTime) -> Option<SystemTime> {
+        let jitter = rand::random_range(0..=self.jitter.as_millis() as u64);
+        Some(self.schedule.next_after(time)? + Duration::from_millis(jitter))
+    }
+}
+
+/// ## JobOptions
+/// Returned by `add_job`, sets how the job runs
+pub struct JobOptions<'a, State, C: BotClient = Client> {
+    pub(crate) data: RefMut<'a, String, JobData<State, C>>,
+}
+
+impl<State, C: BotClient> JobOptions<'_, State, C> {
+    /// Delay every run by a random time up to `jitter`, so jobs of several bots do not hit the same service at once
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.data.jitter = jitter;
+        self
+    }
+
+    /// Start the job even if the previous run is not done yet, by default such runs are skipped
+    pub fn allow_overlap(mut self) -> Self {
+        self.data.allow_overlap = true;
+        self
+    }
+}
+
+/// ## Jobs
+/// Handle to the scheduled jobs, used to run them manually
+#[derive(Clone, Default)]
+pub struct Jobs {
+    pub(crate) triggers: Arc<DashMap<String, Arc<Notify>>>,
+}
+
+impl Jobs {
+    /// Run the job now without changing its schedule, returns `false` if there is no such job.
+    /// The run is skipped like a scheduled one if the job is still running
+    pub fn trigger(&self, name: &str) -> bool {
+        match self.triggers.get(name) {
+            Some(trigger) => {
+                trigger.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+
+    // 2001-09-09 01:46:40 UTC
+    const START: Duration = Duration::from_secs(1_000_000_000);
+
+    #[test]
+    fn next_after() {
+        let start = SystemTime::UNIX_EPOCH + START;
+        assert_eq!(
+            Schedule::every(Duration::from_secs(90)).next_after(start),
+            Some(start + Duration::from_secs(90))
+        );
+
+        let schedule = Schedule::cron("0 */10 * * * *").unwrap();
+        let next = schedule.next_after(start).unwrap();
+        assert_eq!(next, start + Duration::from_secs(200));
+        assert_eq!(
+            schedule.next_after(next),
+            Some(next + Duration::from_secs(600))
+        );
+
+        let schedule = Schedule::cron("0 0 0 1 1 * 2000").unwrap();
+        assert_eq!(schedule.next_after(start), None);
+        assert!(Schedule::cron("every minute").is_err());
+    }
+
+    #[test]
+    fn jitter() {
+        let start = SystemTime::UNIX_EPOCH + START;
+        let mut job = JobData::<(), Client> {
+            schedule: Schedule::every(Duration::from_secs(60)),
+            jitter: Duration::from_secs(5),
+            allow_overlap: false,
+            func: Arc::new(|_: Context<()>| async { anyhow::Ok(()) }),
+            running: Arc::default(),
+            trigger: Arc::default(),
+        };
+        for _ in 0..100 {
+            let delay = job.next_run(start).unwrap().duration_since(start).unwrap();
+            assert!((Duration::from_secs(60)..=Duration::from_secs(65)).contains(&delay));
+        }
+
+        job.jitter = Duration::ZERO;
+        assert_eq!(job.next_run(start), Some(start + Duration::from_secs(60)));
+    }
+}
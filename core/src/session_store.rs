@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use futures::future::BoxFuture;
+use std::{io::ErrorKind, path::PathBuf};
+
+/// ## SessionStore
+/// Place where the serialized Telegram session is kept between restarts
+pub trait SessionStore: Send + Sync {
+    /// Load saved session, `None` if there is nothing saved yet
+    fn load(&self) -> BoxFuture<'_, anyhow::Result<Option<Vec<u8>>>>;
+
+    fn save(&self, data: Vec<u8>) -> BoxFuture<'_, anyhow::Result<()>>;
+}
+
+/// ## FileSessionStore
+/// Keeps the session in a file
+pub struct FileSessionStore {
+    path: PathBuf,
+}
+
+impl FileSessionStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn load(&self) -> BoxFuture<'_, anyhow::Result<Option<Vec<u8>>>> {
+        Box::pin(async move {
+            match tokio::fs::read(&self.path).await {
+                Ok(data) => Ok(Some(data)),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+
+    fn save(&self, data: Vec<u8>) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(async move {
+            let temp = self.path.with_extension("tmp");
+            tokio::fs::write(&temp, data).await?;
+            tokio::fs::rename(&temp, &self.path).await?;
+            Ok(())
+        })
+    }
+}
+
+/// ## SqliteSessionStore
+/// Keeps the session in the `sessions` table of a SQLite database, so it can live next to the rest of the bot data
+#[cfg(feature = "sqlite")]
+pub struct SqliteSessionStore {
+    pool: sqlx::SqlitePool,
+    name: String,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteSessionStore {
+    /// Use the session called `name`
+    pub async fn new(pool: sqlx::SqlitePool, name: impl Into<String>) -> anyhow::Result<Self> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS sessions (name TEXT PRIMARY KEY, data BLOB NOT NULL)",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self {
+            pool,
+            name: name.into(),
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl SessionStore for SqliteSessionStore {
+    fn load(&self) -> BoxFuture<'_, anyhow::Result<Option<Vec<u8>>>> {
+        Box::pin(async move {
+            Ok(
+                sqlx::query_scalar::<_, Vec<u8>>("SELECT data FROM sessions WHERE name = ?")
+                    .bind(&self.name)
+                    .fetch_optional(&self.pool)
+                    .await?,
+            )
+        })
+    }
+
+    fn save(&self, data: Vec<u8>) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO sessions (name, data) VALUES (?, ?) ON CONFLICT (name) DO UPDATE SET data = excluded.data",
+            )
+            .bind(&self.name)
+            .bind(data)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+}
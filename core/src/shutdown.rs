@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use dashmap::DashMap;
+use grammers_client::grammers_tl_types::enums::InputBotInlineMessageId;
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+/// ## PendingInlineMessages
+/// Inline messages that are still being worked on, they get a notice in the language of the user if the bot shuts down before they are done
+#[derive(Clone, Default)]
+pub struct PendingInlineMessages {
+    last_id: Arc<AtomicU64>,
+    messages: Arc<DashMap<u64, (InputBotInlineMessageId, String)>>,
+}
+
+impl PendingInlineMessages {
+    /// Mark message of the user speaking `language` as pending until the returned guard is dropped
+    pub fn add(&self, message_id: InputBotInlineMessageId, language: String) -> PendingGuard {
+        let id = self.last_id.fetch_add(1, Ordering::SeqCst);
+        self.messages.insert(id, (message_id, language));
+        PendingGuard {
+            id,
+            messages: self.messages.clone(),
+        }
+    }
+
+    pub(crate) fn drain(&self) -> Vec<(InputBotInlineMessageId, String)> {
+        let ids: Vec<_> = self.messages.iter().map(|m| *m.key()).collect();
+        ids.into_iter()
+            .filter_map(|id| self.messages.remove(&id).map(|(_, m)| m))
+            .collect()
+    }
+}
+
+/// ## PendingGuard
+/// Removes the message from pending ones when dropped
+pub struct PendingGuard {
+    id: u64,
+    messages: Arc<DashMap<u64, (InputBotInlineMessageId, String)>>,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.messages.remove(&self.id);
+    }
+}
+
+/// Wait for SIGINT or SIGTERM
+pub(crate) async fn signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        let mut terminate = signal(SignalKind::terminate()).expect("failed to listen for SIGTERM");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
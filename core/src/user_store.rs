@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2025 Myst33d <myst33d@gmail.com>
+
+use dashmap::DashMap;
+use futures::future::BoxFuture;
+
+/// ## UserStore
+/// Place where a value per user is kept, e.g. languages chosen with `/language` or roles granted with `/grant`
+pub trait UserStore<T>: Send + Sync {
+    fn load(&self, user_id: i64) -> BoxFuture<'_, anyhow::Result<Option<T>>>;
+
+    fn save(&self, user_id: i64, value: T) -> BoxFuture<'_, anyhow::Result<()>>;
+
+    fn remove(&self, user_id: i64) -> BoxFuture<'_, anyhow::Result<()>>;
+
+    fn list(&self) -> BoxFuture<'_, anyhow::Result<Vec<(i64, T)>>>;
+}
+
+/// ## MemoryUserStore
+/// Keeps values in memory, they are lost on restart
+pub struct MemoryUserStore<T> {
+    values: DashMap<i64, T>,
+}
+
+impl<T> MemoryUserStore<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T> Default for MemoryUserStore<T> {
+    fn default() -> Self {
+        Self {
+            values: DashMap::new(),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> UserStore<T> for MemoryUserStore<T> {
+    fn load(&self, user_id: i64) -> BoxFuture<'_, anyhow::Result<Option<T>>> {
+        let value = self.values.get(&user_id).map(|v| v.clone());
+        Box::pin(async move { Ok(value) })
+    }
+
+    fn save(&self, user_id: i64, value: T) -> BoxFuture<'_, anyhow::Result<()>> {
+        self.values.insert(user_id, value);
+        Box::pin(async move { Ok(()) })
+    }
+
+    fn remove(&self, user_id: i64) -> BoxFuture<'_, anyhow::Result<()>> {
+        self.values.remove(&user_id);
+        Box::pin(async move { Ok(()) })
+    }
+
+    fn list(&self) -> BoxFuture<'_, anyhow::Result<Vec<(i64, T)>>> {
+        let values = self
+            .values
+            .iter()
+            .map(|v| (*v.key(), v.value().clone()))
+            .collect();
+        Box::pin(async move { Ok(values) })
+    }
+}
+
+/// ## SqliteUserStore
+/// Keeps values as text in a SQLite table with a `user_id` column and a value column
+#[cfg(feature = "sqlite")]
+pub struct SqliteUserStore<T> {
+    pool: sqlx::SqlitePool,
+    table: String,
+    column: String,
+    value: std::marker::PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "sqlite")]
+impl<T> SqliteUserStore<T> {
+    /// Keep values in `column` of `table`, e.g. `SqliteUserStore::new(pool, "user_roles", "role")`
+    pub async fn new(pool: sqlx::SqlitePool, table: &str, column: &str) -> anyhow::Result<Self> {
+        for name in [table, column] {
+            anyhow::ensure!(
+                !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+                "`{name}` is not a valid table or column name"
+            );
+        }
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {table} (user_id INTEGER PRIMARY KEY, {column} TEXT NOT NULL)"
+        ))
+        .execute(&pool)
+        .await?;
+        Ok(Self {
+            pool,
+            table: table.to_owned(),
+            column: column.to_owned(),
+            value: std::marker::PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl<T> UserStore<T> for SqliteUserStore<T>
+where
+    T: std::fmt::Display + std::str::FromStr + Send + 'static,
+    T::Err: Into<anyhow::Error>,
+{
+    fn load(&self, user_id: i64) -> BoxFuture<'_, anyhow::Result<Option<T>>> {
+        Box::pin(async move {
+            sqlx::query_scalar::<_, String>(&format!(
+                "SELECT {} FROM {} WHERE user_id = ?",
+                self.column, self.table
+            ))
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(|value| value.parse::<T>().map_err(Into::into))
+            .transpose()
+        })
+    }
+
+    fn save(&self, user_id: i64, value: T) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(async move {
+            sqlx::query(&format!(
+                "INSERT INTO {table} (user_id, {column}) VALUES (?, ?) ON CONFLICT (user_id) DO UPDATE SET {column} = excluded.{column}",
+                table = self.table,
+                column = self.column,
+            ))
+            .bind(user_id)
+            .bind(value.to_string())
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn remove(&self, user_id: i64) -> BoxFuture<'_, anyhow::Result<()>> {
+        Box::pin(async move {
+            sqlx::query(&format!("DELETE FROM {} WHERE user_id = ?", self.table))
+                .bind(user_id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn list(&self) -> BoxFuture<'_, anyhow::Result<Vec<(i64, T)>>> {
+        Box::pin(async move {
+            sqlx::query_as::<_, (i64, String)>(&format!(
+                "SELECT user_id, {} FROM {}",
+                self.column, self.table
+            ))
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|(user_id, value)| Ok((user_id, value.parse::<T>().map_err(Into::into)?)))
+            .collect()
+        })
+    }
+}